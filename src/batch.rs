@@ -0,0 +1,74 @@
+use rmcp::ErrorData as McpError;
+use serde_json::{json, Value};
+
+use crate::SiyuanClient;
+
+const FLUSH_ENDPOINT: &str = "/api/sqlite/flushTransaction";
+
+/// Dispatches an ordered array of block ops in sequence, optionally stopping at the
+/// first failure and only flushing the SQLite transaction on full success.
+///
+/// `atomic` is a stop-on-failure switch, not real atomicity: SiYuan commits each op
+/// independently as it's applied, so a failure partway through leaves every op before
+/// it in place. `flushTransaction` only flushes SiYuan's already-committed state to
+/// disk; it does not roll anything back. Callers that need true rollback must reverse
+/// the successful ops themselves using the returned per-op result array.
+pub async fn handle(client: &SiyuanClient, args: Value) -> Result<Value, McpError> {
+    let ops = args
+        .get("ops")
+        .and_then(|value| value.as_array())
+        .cloned()
+        .ok_or_else(|| McpError::invalid_params("missing or invalid `ops`", None))?;
+    let atomic = args
+        .get("atomic")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+
+    let mut results = Vec::with_capacity(ops.len());
+    let mut any_failed = false;
+    for (index, op) in ops.iter().enumerate() {
+        if atomic && any_failed {
+            break;
+        }
+        match dispatch_op(client, op).await {
+            Ok(result) => results.push(json!({ "index": index, "ok": true, "result": result })),
+            Err(err) => {
+                results.push(json!({ "index": index, "ok": false, "error": err.to_string() }));
+                any_failed = true;
+            }
+        }
+    }
+
+    if atomic && !any_failed {
+        client.post_json_value(FLUSH_ENDPOINT, json!({})).await?;
+    }
+
+    Ok(json!({
+        "results": results,
+        "failed": any_failed,
+    }))
+}
+
+async fn dispatch_op(client: &SiyuanClient, op: &Value) -> Result<Value, McpError> {
+    let kind = op
+        .get("op")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| McpError::invalid_params("missing or invalid `op`", None))?;
+    let endpoint = match kind {
+        "insert" => "/api/block/insertBlock",
+        "update" => "/api/block/updateBlock",
+        "delete" => "/api/block/deleteBlock",
+        "move" => "/api/block/moveBlock",
+        other => {
+            return Err(McpError::invalid_params(
+                format!("unknown batch op `{}`", other),
+                None,
+            ))
+        }
+    };
+    let mut body = op.clone();
+    if let Some(map) = body.as_object_mut() {
+        map.remove("op");
+    }
+    client.post_json_value(endpoint, body).await
+}
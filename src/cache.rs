@@ -0,0 +1,210 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Endpoints known to be side-effect free and whose result is stable enough to
+/// reuse for the cache TTL. `bootProgress`/`currentTime` are deliberately excluded
+/// even though they're side-effect free: they're expected to change on every call,
+/// so caching them would mean literally never returning a fresh answer.
+const CACHEABLE_ENDPOINTS: &[&str] = &[
+    "/api/query/sql",
+    "/api/filetree/getHPathByPath",
+    "/api/filetree/getHPathByID",
+    "/api/filetree/getPathByID",
+    "/api/filetree/getIDsByHPath",
+    "/api/block/getBlockKramdown",
+    "/api/block/getChildBlocks",
+    "/api/attr/getBlockAttrs",
+    "/api/file/readDir",
+    "/api/notebook/lsNotebooks",
+    "/api/notebook/getNotebookConf",
+    "/api/system/version",
+];
+
+pub fn is_cacheable_endpoint(endpoint: &str) -> bool {
+    CACHEABLE_ENDPOINTS.contains(&endpoint)
+}
+
+/// SiYuan's convention: a JSON-RPC-ish envelope with `code == 0` on success and a
+/// nonzero `code` (still HTTP 200) on a logical/kernel error. Only success responses
+/// are safe to serve back for the rest of the TTL.
+pub fn is_success_response(value: &Value) -> bool {
+    value.get("code").and_then(Value::as_i64) == Some(0)
+}
+
+/// Keyed by `(endpoint, canonicalized args)`, not `(tool_name, canonicalized args)` —
+/// several tools share one endpoint (e.g. `siyuan_sql_query`, `siyuan_search` and
+/// `siyuan_index_build` all post to `/api/query/sql`), so this is coarser than a per-tool
+/// key but still correct since the endpoint plus args fully determines the response.
+pub fn cache_key(endpoint: &str, args: &Value) -> String {
+    format!("{}\u{0}{}", endpoint, canonicalize(args))
+}
+
+fn canonicalize(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let sorted: BTreeMap<String, Value> = map
+                .iter()
+                .map(|(key, val)| (key.clone(), canonicalize(val)))
+                .collect();
+            Value::Object(sorted.into_iter().collect())
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonicalize).collect()),
+        other => other.clone(),
+    }
+}
+
+pub trait ResponseCache: Send + Sync {
+    fn get(&self, key: &str) -> Option<Value>;
+    fn put(&self, key: &str, value: Value);
+    /// Drops every cached entry. Called when a mutating tool succeeds, since the
+    /// cache has no finer-grained way to know which reads it may have invalidated.
+    fn invalidate_all(&self);
+}
+
+pub struct NoCache;
+
+impl ResponseCache for NoCache {
+    fn get(&self, _key: &str) -> Option<Value> {
+        None
+    }
+
+    fn put(&self, _key: &str, _value: Value) {}
+
+    fn invalidate_all(&self) {}
+}
+
+struct MemoryCacheState {
+    entries: HashMap<String, (Value, Instant)>,
+    order: VecDeque<String>,
+}
+
+pub struct MemoryCache {
+    capacity: usize,
+    ttl_ms: u64,
+    state: Mutex<MemoryCacheState>,
+}
+
+impl MemoryCache {
+    pub fn new(capacity: usize, ttl_ms: u64) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ttl_ms,
+            state: Mutex::new(MemoryCacheState {
+                entries: HashMap::new(),
+                order: VecDeque::new(),
+            }),
+        }
+    }
+
+    fn is_expired(&self, inserted_at: Instant) -> bool {
+        self.ttl_ms > 0 && inserted_at.elapsed() > Duration::from_millis(self.ttl_ms)
+    }
+}
+
+impl ResponseCache for MemoryCache {
+    fn get(&self, key: &str) -> Option<Value> {
+        let mut state = self.state.lock().expect("memory cache lock poisoned");
+        let (value, inserted_at) = state.entries.get(key)?.clone();
+        if self.is_expired(inserted_at) {
+            state.entries.remove(key);
+            state.order.retain(|existing| existing != key);
+            return None;
+        }
+        state.order.retain(|existing| existing != key);
+        state.order.push_back(key.to_string());
+        Some(value)
+    }
+
+    fn put(&self, key: &str, value: Value) {
+        let mut state = self.state.lock().expect("memory cache lock poisoned");
+        state.entries.insert(key.to_string(), (value, Instant::now()));
+        state.order.retain(|existing| existing != key);
+        state.order.push_back(key.to_string());
+        while state.entries.len() > self.capacity {
+            let Some(oldest) = state.order.pop_front() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+    }
+
+    fn invalidate_all(&self) {
+        let mut state = self.state.lock().expect("memory cache lock poisoned");
+        state.entries.clear();
+        state.order.clear();
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct FsEntry {
+    value: Value,
+    inserted_at_ms: u128,
+}
+
+/// One JSON file per entry on disk. There is no sqlite-backed `ResponseCache` impl;
+/// `--cache` only chooses between this, `MemoryCache`, and `NoCache`.
+pub struct FsCache {
+    dir: PathBuf,
+    ttl_ms: u64,
+}
+
+impl FsCache {
+    pub fn new(dir: PathBuf, ttl_ms: u64) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir, ttl_ms })
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", hash_key(key)))
+    }
+}
+
+impl ResponseCache for FsCache {
+    fn get(&self, key: &str) -> Option<Value> {
+        let bytes = std::fs::read(self.path_for(key)).ok()?;
+        let entry: FsEntry = serde_json::from_slice(&bytes).ok()?;
+        if self.ttl_ms > 0 {
+            let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_millis();
+            if now_ms.saturating_sub(entry.inserted_at_ms) > self.ttl_ms as u128 {
+                let _ = std::fs::remove_file(self.path_for(key));
+                return None;
+            }
+        }
+        Some(entry.value)
+    }
+
+    fn put(&self, key: &str, value: Value) {
+        let inserted_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis())
+            .unwrap_or(0);
+        let entry = FsEntry {
+            value,
+            inserted_at_ms,
+        };
+        if let Ok(bytes) = serde_json::to_vec(&entry) {
+            let _ = std::fs::write(self.path_for(key), bytes);
+        }
+    }
+
+    fn invalidate_all(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+fn hash_key(key: &str) -> String {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    key.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
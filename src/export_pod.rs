@@ -0,0 +1,220 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use base64::{engine::general_purpose, Engine as _};
+use regex::Regex;
+use rmcp::ErrorData as McpError;
+use serde_json::{json, Value};
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+use crate::SiyuanClient;
+
+/// Exports a doc's Markdown plus every `assets/...` file it references as a single zip.
+pub async fn handle(client: &SiyuanClient, export_md_endpoint: &str, args: Value) -> Result<Value, McpError> {
+    let id = args
+        .get("id")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| McpError::invalid_params("missing or invalid `id`", None))?
+        .to_string();
+
+    let exported = client
+        .post_json_value(export_md_endpoint, json!({ "id": id }))
+        .await?;
+    let data = exported.get("data").cloned().unwrap_or(Value::Null);
+    let content = data
+        .get("content")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| McpError::internal_error("exportMdContent returned no content", None))?
+        .to_string();
+    let h_path = data
+        .get("hPath")
+        .and_then(|value| value.as_str())
+        .unwrap_or(&id)
+        .to_string();
+    let title = std::path::Path::new(&h_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(&id)
+        .to_string();
+
+    let asset_paths = extract_asset_paths(&content);
+    let mut missing = Vec::new();
+    let mut buffer = Vec::new();
+    {
+        let cursor = std::io::Cursor::new(&mut buffer);
+        let mut zip = ZipWriter::new(cursor);
+        let options: FileOptions<()> = FileOptions::default();
+
+        zip.start_file(format!("{}.md", title), options)
+            .map_err(zip_err)?;
+        zip.write_all(content.as_bytes()).map_err(io_err)?;
+
+        for path in asset_paths {
+            let workspace_path = to_workspace_path(&path);
+            let fetched = client
+                .post_json_file("/api/file/getFile", json!({ "path": workspace_path }))
+                .await?;
+            match fetched.get("body_base64").and_then(|value| value.as_str()) {
+                Some(encoded) => {
+                    let bytes = general_purpose::STANDARD
+                        .decode(encoded)
+                        .map_err(|err| McpError::internal_error(err.to_string(), None))?;
+                    zip.start_file(path.trim_start_matches('/'), options)
+                        .map_err(zip_err)?;
+                    zip.write_all(&bytes).map_err(io_err)?;
+                }
+                None => missing.push(path),
+            }
+        }
+
+        zip.finish().map_err(zip_err)?;
+    }
+
+    Ok(json!({
+        "filename": format!("{}.zip", title),
+        "zip_base64": general_purpose::STANDARD.encode(&buffer),
+        "missing": missing,
+    }))
+}
+
+/// `/api/file/getFile` is workspace-relative, so a doc-relative `assets/...` path
+/// must be resolved under `/data/` before it resolves to anything on disk.
+fn to_workspace_path(path: &str) -> String {
+    format!("/data/{}", path.trim_start_matches('/'))
+}
+
+fn extract_asset_paths(content: &str) -> Vec<String> {
+    let direct = Regex::new(r#"assets/[^\s)\]"']+"#).expect("valid regex");
+    let block_ref = Regex::new(r"\(\(([^()]+)\)\)").expect("valid regex");
+    let wiki_link = Regex::new(r"\[\[([^\[\]]+)\]\]").expect("valid regex");
+
+    let mut seen = HashSet::new();
+    let mut paths = Vec::new();
+
+    for found in direct.find_iter(content) {
+        if seen.insert(found.as_str().to_string()) {
+            paths.push(found.as_str().to_string());
+        }
+    }
+    for captures in block_ref
+        .captures_iter(content)
+        .chain(wiki_link.captures_iter(content))
+    {
+        let Some(inner) = captures.get(1) else {
+            continue;
+        };
+        let Some(start) = inner.as_str().find("assets/") else {
+            continue;
+        };
+        let candidate = inner.as_str()[start..].to_string();
+        if seen.insert(candidate.clone()) {
+            paths.push(candidate);
+        }
+    }
+    paths
+}
+
+fn zip_err(err: zip::result::ZipError) -> McpError {
+    McpError::internal_error(err.to_string(), None)
+}
+
+fn io_err(err: std::io::Error) -> McpError {
+    McpError::internal_error(err.to_string(), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{cache::NoCache, SiyuanClient};
+
+    #[test]
+    fn resolves_asset_paths_under_data() {
+        assert_eq!(to_workspace_path("assets/pic.png"), "/data/assets/pic.png");
+        assert_eq!(to_workspace_path("/assets/pic.png"), "/data/assets/pic.png");
+    }
+
+    /// Reads one HTTP/1.1 request off `stream` and replies with a canned 200 JSON body.
+    fn respond_json(stream: &mut std::net::TcpStream, body: &str) {
+        let mut reader = BufReader::new(stream.try_clone().expect("clone stream"));
+        let mut content_length = 0usize;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).expect("read header line");
+            if line == "\r\n" || line.is_empty() {
+                break;
+            }
+            let lower = line.to_ascii_lowercase();
+            if let Some(value) = lower.strip_prefix("content-length:") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+        let mut discard = vec![0u8; content_length];
+        reader.read_exact(&mut discard).expect("read request body");
+
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).expect("write response");
+    }
+
+    #[tokio::test]
+    async fn handle_bundles_referenced_asset_into_zip() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let port = listener.local_addr().expect("local addr").port();
+
+        let asset_bytes = b"pretend-png-bytes";
+        let asset_b64 = general_purpose::STANDARD.encode(asset_bytes);
+        std::thread::spawn(move || {
+            // exportMdContent
+            let (mut stream, _) = listener.accept().expect("accept export request");
+            respond_json(
+                &mut stream,
+                r#"{"data":{"content":"![pic](assets/pic.png)","hPath":"/notes/test.md"}}"#,
+            );
+
+            // getFile — the request body is not inspected here; the /data/ path
+            // resolution is covered by `resolves_asset_paths_under_data`.
+            let (mut stream, _) = listener.accept().expect("accept getFile request");
+            respond_json(
+                &mut stream,
+                &format!(
+                    r#"{{"status":200,"content_type":"image/png","body_base64":"{}"}}"#,
+                    asset_b64
+                ),
+            );
+        });
+
+        let client = SiyuanClient::new(
+            format!("http://127.0.0.1:{}", port),
+            None,
+            5_000,
+            Arc::new(NoCache),
+            None,
+        )
+        .expect("build client");
+
+        let result = handle(&client, "/api/export/exportMdContent", json!({ "id": "20240101" }))
+            .await
+            .expect("handle succeeds");
+
+        assert_eq!(result["missing"].as_array().map(Vec::len), Some(0));
+        let zip_bytes = general_purpose::STANDARD
+            .decode(result["zip_base64"].as_str().expect("zip_base64 present"))
+            .expect("valid base64");
+        let mut archive =
+            zip::ZipArchive::new(std::io::Cursor::new(zip_bytes)).expect("open zip archive");
+        let mut asset_file = archive
+            .by_name("assets/pic.png")
+            .expect("asset present in zip");
+        let mut contents = Vec::new();
+        asset_file.read_to_end(&mut contents).expect("read asset bytes");
+        assert_eq!(contents, asset_bytes);
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use rmcp::ErrorData as McpError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::SiyuanServer;
+
+pub type JobId = String;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum JobState {
+    Queued,
+    Running,
+    Paused,
+    Completed { result: Value },
+    Failed { error: String },
+}
+
+impl JobState {
+    fn is_terminal(&self) -> bool {
+        matches!(self, JobState::Completed { .. } | JobState::Failed { .. })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub id: JobId,
+    pub tool_name: String,
+    pub args: Value,
+    pub state: JobState,
+    pub progress: f64,
+}
+
+impl JobRecord {
+    fn is_resumable(&self) -> bool {
+        matches!(self.state, JobState::Queued | JobState::Running)
+    }
+}
+
+pub struct JobManager {
+    state_file: PathBuf,
+    jobs: Mutex<HashMap<JobId, JobRecord>>,
+    /// Abort handles for in-flight tasks, keyed by job id, so `cancel` actually stops
+    /// the spawned task instead of only flipping the record's state.
+    handles: std::sync::Mutex<HashMap<JobId, tokio::task::AbortHandle>>,
+}
+
+impl JobManager {
+    pub fn load(state_file: PathBuf) -> Arc<Self> {
+        let jobs = std::fs::read(&state_file)
+            .ok()
+            .and_then(|bytes| rmp_serde::from_slice::<HashMap<JobId, JobRecord>>(&bytes).ok())
+            .unwrap_or_default();
+        Arc::new(Self {
+            state_file,
+            jobs: Mutex::new(jobs),
+            handles: std::sync::Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub async fn pending_jobs(&self) -> Vec<JobRecord> {
+        self.jobs
+            .lock()
+            .await
+            .values()
+            .filter(|record| record.is_resumable())
+            .cloned()
+            .collect()
+    }
+
+    async fn persist_locked(&self, jobs: &HashMap<JobId, JobRecord>) {
+        if let Ok(bytes) = rmp_serde::to_vec(jobs) {
+            let _ = tokio::fs::write(&self.state_file, bytes).await;
+        }
+    }
+
+    pub async fn register(&self, tool_name: String, args: Value) -> JobId {
+        let id = Uuid::new_v4().to_string();
+        let record = JobRecord {
+            id: id.clone(),
+            tool_name,
+            args,
+            state: JobState::Queued,
+            progress: 0.0,
+        };
+        let mut jobs = self.jobs.lock().await;
+        jobs.insert(id.clone(), record);
+        self.persist_locked(&jobs).await;
+        id
+    }
+
+    /// Refuses to overwrite a terminal state, so a completion/failure racing a
+    /// `cancel` can't resurrect a job the caller already gave up on.
+    async fn set_state(&self, id: &str, state: JobState) {
+        let mut jobs = self.jobs.lock().await;
+        let changed = match jobs.get_mut(id) {
+            Some(record) if !record.state.is_terminal() => {
+                record.state = state;
+                true
+            }
+            _ => false,
+        };
+        if changed {
+            self.persist_locked(&jobs).await;
+        }
+    }
+
+    pub fn run(self: &Arc<Self>, id: JobId, server: SiyuanServer, tool_name: String, args: Value) {
+        let manager = self.clone();
+        let handle_id = id.clone();
+        let join_handle = tokio::spawn(async move {
+            manager.set_state(&id, JobState::Running).await;
+            match server.dispatch_tool(&tool_name, args).await {
+                Ok(result) => manager.set_state(&id, JobState::Completed { result }).await,
+                Err(err) => {
+                    manager
+                        .set_state(&id, JobState::Failed { error: err.to_string() })
+                        .await
+                }
+            }
+            manager
+                .handles
+                .lock()
+                .expect("job handles mutex poisoned")
+                .remove(&id);
+        });
+        self.handles
+            .lock()
+            .expect("job handles mutex poisoned")
+            .insert(handle_id, join_handle.abort_handle());
+    }
+
+    pub async fn status(&self, id: &str) -> Option<JobRecord> {
+        self.jobs.lock().await.get(id).cloned()
+    }
+
+    pub async fn cancel(&self, id: &str) -> bool {
+        let mut jobs = self.jobs.lock().await;
+        match jobs.get_mut(id) {
+            Some(record) if record.is_resumable() => {
+                record.state = JobState::Failed {
+                    error: "cancelled".to_string(),
+                };
+                self.persist_locked(&jobs).await;
+                if let Some(handle) = self
+                    .handles
+                    .lock()
+                    .expect("job handles mutex poisoned")
+                    .remove(id)
+                {
+                    handle.abort();
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+pub async fn handle_submit(server: &SiyuanServer, args: Value) -> Result<Value, McpError> {
+    let tool_name = args
+        .get("tool_name")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| McpError::invalid_params("missing or invalid `tool_name`", None))?
+        .to_string();
+    let tool_args = args.get("args").cloned().unwrap_or_else(|| json!({}));
+
+    let id = server.jobs.register(tool_name.clone(), tool_args.clone()).await;
+    server
+        .jobs
+        .run(id.clone(), server.clone(), tool_name, tool_args);
+    Ok(json!({ "job_id": id }))
+}
+
+pub async fn handle_status(server: &SiyuanServer, args: Value) -> Result<Value, McpError> {
+    let id = job_id_arg(&args)?;
+    let record = server
+        .jobs
+        .status(&id)
+        .await
+        .ok_or_else(|| McpError::invalid_params(format!("unknown job `{}`", id), None))?;
+    serde_json::to_value(record).map_err(|err| McpError::internal_error(err.to_string(), None))
+}
+
+pub async fn handle_cancel(server: &SiyuanServer, args: Value) -> Result<Value, McpError> {
+    let id = job_id_arg(&args)?;
+    let cancelled = server.jobs.cancel(&id).await;
+    Ok(json!({ "job_id": id, "cancelled": cancelled }))
+}
+
+fn job_id_arg(args: &Value) -> Result<String, McpError> {
+    args.get("job_id")
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string())
+        .ok_or_else(|| McpError::invalid_params("missing or invalid `job_id`", None))
+}
+
+/// Re-runs `Queued`/`Running` jobs left over from a crash. Jobs wrapping a tool in
+/// `MUTATING_TOOLS` (e.g. `siyuan_block_insert`) are not idempotent — resubmitting one
+/// after it already applied its side effect would double-apply it — so those are
+/// marked failed instead of re-dispatched. Every other job is re-dispatched, including
+/// writes outside that list such as `siyuan_doc_create_md` or `siyuan_notebook_create`.
+pub async fn resume_pending(server: &SiyuanServer) {
+    for record in server.jobs.pending_jobs().await {
+        if crate::is_mutating_tool(&record.tool_name) {
+            server
+                .jobs
+                .set_state(
+                    &record.id,
+                    JobState::Failed {
+                        error: "not resumed after restart: tool has non-idempotent side effects"
+                            .to_string(),
+                    },
+                )
+                .await;
+            continue;
+        }
+        server
+            .jobs
+            .run(record.id, server.clone(), record.tool_name, record.args);
+    }
+}
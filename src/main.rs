@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
@@ -6,6 +7,7 @@ use std::time::Duration;
 use anyhow::Context;
 use base64::{engine::general_purpose, Engine as _};
 use clap::Parser;
+use jsonschema::JSONSchema;
 use log::{debug, info};
 use rmcp::{
     ErrorData as McpError,
@@ -16,12 +18,23 @@ use rmcp::{
         CallToolRequestParam, CallToolResult, Content, Implementation, JsonObject,
         ListToolsResult, PaginatedRequestParam, ServerCapabilities, ServerInfo, Tool,
     },
-    service::RequestContext,
+    service::{Peer, RequestContext},
     transport::stdio,
 };
 use reqwest::multipart::{Form, Part};
 use serde_json::{json, Value};
 
+mod batch;
+mod cache;
+mod export_pod;
+mod jobs;
+mod metrics;
+mod search;
+mod semantic;
+mod tools_manifest;
+mod transport_sse;
+mod watch;
+
 #[derive(Debug, Parser)]
 #[command(name = "siyuan-mcp", version, about = "SiYuan MCP server")]
 struct Args {
@@ -31,6 +44,51 @@ struct Args {
     token: Option<String>,
     #[arg(long, env = "SIYUAN_TIMEOUT_MS", default_value_t = 15000)]
     timeout_ms: u64,
+    #[arg(long, default_value_t = false)]
+    default_dry_run: bool,
+    #[arg(long, value_enum, default_value_t = TransportKind::Stdio)]
+    transport: TransportKind,
+    #[arg(long, default_value = "127.0.0.1:8765")]
+    bind: String,
+    #[arg(long)]
+    auth_token: Option<String>,
+    #[arg(long = "cors-origin")]
+    cors_origins: Vec<String>,
+    #[arg(long)]
+    tools_file: Option<std::path::PathBuf>,
+    #[arg(long, value_enum, default_value_t = CacheBackendArg::None)]
+    cache: CacheBackendArg,
+    #[arg(long)]
+    cache_dir: Option<std::path::PathBuf>,
+    #[arg(long, default_value_t = 0)]
+    cache_ttl_ms: u64,
+    #[arg(long, default_value = ".siyuan-mcp-jobs.mpk")]
+    jobs_state_file: std::path::PathBuf,
+    #[arg(long)]
+    metrics_addr: Option<String>,
+    #[arg(long, default_value = "http://127.0.0.1:6806/v1/embeddings")]
+    embeddings_endpoint: String,
+    #[arg(long, default_value = "text-embedding-3-small")]
+    embeddings_model: String,
+    #[arg(long, default_value = ".siyuan-mcp-semantic-index.json")]
+    semantic_index_file: std::path::PathBuf,
+    #[arg(long, default_value_t = 0)]
+    watch_interval_ms: u64,
+    #[arg(long, default_value = ".siyuan-mcp-watch.json")]
+    watch_state_file: std::path::PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum CacheBackendArg {
+    None,
+    Memory,
+    Fs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum TransportKind {
+    Stdio,
+    Sse,
 }
 
 #[derive(Clone)]
@@ -38,10 +96,18 @@ struct SiyuanClient {
     base_url: String,
     token: Option<String>,
     client: reqwest::Client,
+    cache: Arc<dyn cache::ResponseCache>,
+    metrics: Option<Arc<metrics::Metrics>>,
 }
 
 impl SiyuanClient {
-    fn new(base_url: String, token: Option<String>, timeout_ms: u64) -> anyhow::Result<Self> {
+    fn new(
+        base_url: String,
+        token: Option<String>,
+        timeout_ms: u64,
+        cache: Arc<dyn cache::ResponseCache>,
+        metrics: Option<Arc<metrics::Metrics>>,
+    ) -> anyhow::Result<Self> {
         let client = reqwest::Client::builder()
             .timeout(Duration::from_millis(timeout_ms))
             .build()
@@ -50,15 +116,34 @@ impl SiyuanClient {
             base_url: base_url.trim_end_matches('/').to_string(),
             token,
             client,
+            cache,
+            metrics,
         })
     }
 
-    async fn post_json_value(&self, endpoint: &str, body: Value) -> Result<Value, McpError> {
+    async fn post_json_value(&self, endpoint: &str, mut body: Value) -> Result<Value, McpError> {
+        let cache_bypass = body
+            .as_object_mut()
+            .and_then(|map| map.remove("cache_bypass"))
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false);
+        let cache_key = (!cache_bypass && cache::is_cacheable_endpoint(endpoint))
+            .then(|| cache::cache_key(endpoint, &body));
+        if let Some(key) = &cache_key {
+            if let Some(cached) = self.cache.get(key) {
+                return Ok(cached);
+            }
+        }
+
         let url = format!("{}{}", self.base_url, endpoint);
         let mut req = self.client.post(url).json(&body);
         if let Some(token) = &self.token {
             req = req.header("Authorization", format!("Token {}", token));
         }
+        // Timed around only the upstream call (not the caller's surrounding local
+        // work, e.g. BM25 re-ranking or zip assembly), since `endpoint_latency` is
+        // meant to answer "is SiYuan itself slow", not "is this tool handler slow".
+        let started_at = std::time::Instant::now();
         let resp = req
             .send()
             .await
@@ -68,10 +153,19 @@ impl SiyuanClient {
             .text()
             .await
             .map_err(|err| McpError::internal_error(err.to_string(), None))?;
-        match serde_json::from_str::<Value>(&text) {
-            Ok(value) => Ok(value),
-            Err(_) => Ok(json!({ "status": status.as_u16(), "body": text })),
+        if let Some(metrics) = &self.metrics {
+            metrics.observe_endpoint_latency(endpoint, started_at.elapsed());
+        }
+        let value = match serde_json::from_str::<Value>(&text) {
+            Ok(value) => value,
+            Err(_) => json!({ "status": status.as_u16(), "body": text }),
+        };
+        if let Some(key) = &cache_key {
+            if cache::is_success_response(&value) {
+                self.cache.put(key, value.clone());
+            }
         }
+        Ok(value)
     }
 
     async fn post_multipart_value(&self, endpoint: &str, form: Form) -> Result<Value, McpError> {
@@ -141,33 +235,85 @@ enum ToolKind {
     AssetUpload,
     PutFile,
     GetFile,
+    ExportPod,
+    Search,
+    Batch,
+    IndexBuild,
+    SemanticSearch,
+}
+
+/// Tools whose success invalidates the read cache, since their writes can change
+/// the answer to any previously cached read. Read-only lookalikes (e.g. `_get_`,
+/// `_read_dir`) are deliberately excluded so a cache-warm poll loop doesn't thrash it.
+const MUTATING_TOOLS: &[&str] = &[
+    "siyuan_block_insert",
+    "siyuan_block_prepend",
+    "siyuan_block_append",
+    "siyuan_block_update",
+    "siyuan_block_delete",
+    "siyuan_block_move",
+    "siyuan_block_batch",
+    "siyuan_block_fold",
+    "siyuan_block_unfold",
+    "siyuan_block_transfer_ref",
+    "siyuan_attr_set",
+    "siyuan_file_put",
+    "siyuan_file_remove",
+    "siyuan_file_rename",
+];
+
+fn is_mutating_tool(name: &str) -> bool {
+    MUTATING_TOOLS.contains(&name)
 }
 
 #[derive(Clone)]
 struct SiyuanTool {
+    name: Cow<'static, str>,
     client: Arc<SiyuanClient>,
-    endpoint: &'static str,
+    endpoint: Cow<'static, str>,
     kind: ToolKind,
+    validator: Arc<JSONSchema>,
+    default_dry_run: bool,
+    semantic_index: Arc<semantic::SemanticIndex>,
 }
 
 impl SiyuanTool {
-    fn new(client: Arc<SiyuanClient>, spec: &ToolSpec) -> Self {
+    fn new(
+        client: Arc<SiyuanClient>,
+        spec: &ToolSpec,
+        schema: &JsonObject,
+        default_dry_run: bool,
+        semantic_index: Arc<semantic::SemanticIndex>,
+    ) -> Self {
+        let validator = JSONSchema::compile(&Value::Object(schema.clone()))
+            .expect("tool schema must compile");
         Self {
+            name: spec.name.clone(),
             client,
-            endpoint: spec.endpoint,
+            endpoint: spec.endpoint.clone(),
             kind: spec.kind,
+            validator: Arc::new(validator),
+            default_dry_run,
+            semantic_index,
         }
     }
 
-    fn ensure_object(args: Value) -> Result<Value, McpError> {
-        match args {
-            Value::Object(_) => Ok(args),
-            Value::Null => Ok(json!({})),
-            _ => Err(McpError::invalid_params(
-                "arguments must be a JSON object",
-                None,
-            )),
+    fn validate(&self, instance: &Value) -> Result<(), McpError> {
+        if let Err(errors) = self.validator.validate(instance) {
+            let details: Vec<Value> = errors
+                .map(|err| {
+                    json!({
+                        "instance_path": err.instance_path.to_string(),
+                        "reason": err.to_string(),
+                    })
+                })
+                .collect();
+            return Err(McpError::invalid_params(
+                "arguments failed schema validation",
+                Some(json!({ "errors": details })),
+            ));
         }
+        Ok(())
     }
 
     fn args_as_object(args: Value) -> Result<serde_json::Map<String, Value>, McpError> {
@@ -249,7 +395,7 @@ impl SiyuanTool {
             let part = Self::file_part(&file_path).await?;
             form = form.part("file[]", part);
         }
-        self.client.post_multipart_value(self.endpoint, form).await
+        self.client.post_multipart_value(&self.endpoint, form).await
     }
 
     async fn handle_put_file(&self, args: Value) -> Result<Value, McpError> {
@@ -270,43 +416,93 @@ impl SiyuanTool {
             let part = Self::file_part(&file_path).await?;
             form = form.part("file", part);
         }
-        self.client.post_multipart_value(self.endpoint, form).await
+        self.client.post_multipart_value(&self.endpoint, form).await
     }
 
     async fn handle_get_file(&self, args: Value) -> Result<Value, McpError> {
         let map = Self::args_as_object(args)?;
         let path = Self::required_string(&map, "path")?;
         let body = json!({ "path": path });
-        self.client.post_json_file(self.endpoint, body).await
+        self.client.post_json_file(&self.endpoint, body).await
     }
     async fn handle(&self, args: Value) -> Result<Value, McpError> {
-        match self.kind {
-            ToolKind::Json => {
-                let body = Self::ensure_object(args)?;
-                self.client.post_json_value(self.endpoint, body).await
+        let mut map = Self::args_as_object(args)?;
+        let dry_run = Self::optional_bool(&map, "dry_run").unwrap_or(self.default_dry_run);
+        map.remove("dry_run");
+        let normalized = Value::Object(map);
+        self.validate(&normalized)?;
+        if dry_run {
+            return Ok(json!({
+                "valid": true,
+                "would_post": &self.endpoint,
+                "body": normalized,
+            }));
+        }
+        let result = match self.kind {
+            ToolKind::Json => self.client.post_json_value(&self.endpoint, normalized).await,
+            ToolKind::AssetUpload => self.handle_asset_upload(normalized).await,
+            ToolKind::PutFile => self.handle_put_file(normalized).await,
+            ToolKind::GetFile => self.handle_get_file(normalized).await,
+            ToolKind::ExportPod => {
+                export_pod::handle(&self.client, &self.endpoint, normalized).await
+            }
+            ToolKind::Search => search::handle(&self.client, &self.endpoint, normalized).await,
+            ToolKind::Batch => batch::handle(&self.client, normalized).await,
+            ToolKind::IndexBuild => {
+                semantic::handle_build(&self.client, &self.endpoint, &self.semantic_index, normalized)
+                    .await
             }
-            ToolKind::AssetUpload => self.handle_asset_upload(args).await,
-            ToolKind::PutFile => self.handle_put_file(args).await,
-            ToolKind::GetFile => self.handle_get_file(args).await,
+            ToolKind::SemanticSearch => {
+                semantic::handle_search(&self.semantic_index, normalized).await
+            }
+        };
+        if result.is_ok() && is_mutating_tool(&self.name) {
+            // Whole-cache flush, not the per-ID invalidation the read-through design
+            // ultimately wants — the cache has no index from a mutated block/path back
+            // to the cache keys it could have affected, so this is the safe fallback.
+            self.client.cache.invalidate_all();
         }
+        result
     }
 }
 
+#[derive(Clone)]
 struct ToolSpec {
-    name: &'static str,
-    endpoint: &'static str,
+    name: Cow<'static, str>,
+    endpoint: Cow<'static, str>,
     kind: ToolKind,
-    description: &'static str,
-    schema: &'static str,
+    description: Cow<'static, str>,
+    schema: Cow<'static, str>,
 }
 
-fn parse_schema(schema: &'static str) -> JsonObject {
+fn parse_schema(schema: &str) -> JsonObject {
     match serde_json::from_str::<Value>(schema) {
         Ok(Value::Object(map)) => map,
         _ => JsonObject::default(),
     }
 }
 
+fn with_dry_run_property(mut schema: JsonObject) -> JsonObject {
+    let properties = schema
+        .entry("properties".to_string())
+        .or_insert_with(|| Value::Object(serde_json::Map::new()));
+    if let Value::Object(props) = properties {
+        props.entry("dry_run".to_string()).or_insert_with(|| {
+            json!({
+                "type": "boolean",
+                "description": "Validate arguments and return the would-be request without contacting SiYuan"
+            })
+        });
+        props.entry("cache_bypass".to_string()).or_insert_with(|| {
+            json!({
+                "type": "boolean",
+                "description": "Skip the response cache and force a fresh request (no-op for non-cacheable tools)"
+            })
+        });
+    }
+    schema
+}
+
 const SCHEMA_EMPTY: &str =
     r#"{"type":"object","properties":{},"additionalProperties":false}"#;
 const SCHEMA_NOTEBOOK_ID: &str = r#"{"type":"object","properties":{"notebook":{"type":"string","description":"Notebook ID"}},"required":["notebook"],"additionalProperties":true}"#;
@@ -340,404 +536,527 @@ const SCHEMA_EXPORT_RESOURCES: &str = r#"{"type":"object","properties":{"paths":
 const SCHEMA_PANDOC: &str = r#"{"type":"object","properties":{"dir":{"type":"string","description":"Working directory name"},"args":{"type":"array","items":{"type":"string"},"description":"Pandoc CLI args"}},"required":["dir","args"],"additionalProperties":true}"#;
 const SCHEMA_NOTIFY: &str = r#"{"type":"object","properties":{"msg":{"type":"string","description":"Message text"},"timeout":{"type":"integer","description":"Timeout in ms"}},"required":["msg"],"additionalProperties":true}"#;
 const SCHEMA_NETWORK_FORWARD_PROXY: &str = r#"{"type":"object","properties":{"url":{"type":"string","description":"Target URL"},"method":{"type":"string","description":"HTTP method"},"timeout":{"type":"integer","description":"Timeout in ms"},"contentType":{"type":"string","description":"Content-Type"},"headers":{"type":"array","items":{"type":"object"},"description":"Headers list"},"payload":{"type":"object","description":"Payload object or string"},"payloadEncoding":{"type":"string","description":"Payload encoding"},"responseEncoding":{"type":"string","description":"Response body encoding"}},"required":["url"],"additionalProperties":true}"#;
+const SCHEMA_BLOCK_BATCH: &str = r#"{"type":"object","properties":{"ops":{"type":"array","items":{"type":"object"},"description":"Ordered array of {op:\"insert\"|\"update\"|\"delete\"|\"move\", ...anchors}"},"atomic":{"type":"boolean","description":"Stop on first failure; only flush the transaction on full success"}},"required":["ops"],"additionalProperties":true}"#;
+const SCHEMA_SEARCH: &str = r#"{"type":"object","properties":{"query":{"type":"string","description":"Search query text"},"limit":{"type":"integer","description":"Max hits to return"},"highlight":{"type":"boolean","description":"Return a snippet around the best match"}},"required":["query"],"additionalProperties":true}"#;
+const SCHEMA_INDEX_BUILD: &str = r#"{"type":"object","properties":{"stmt":{"type":"string","description":"Optional SQL override selecting blocks to index (defaults to all blocks)"}},"additionalProperties":true}"#;
+const SCHEMA_SEMANTIC_SEARCH: &str = r#"{"type":"object","properties":{"query":{"type":"string","description":"Natural-language query to embed and search"},"top_k":{"type":"integer","description":"Number of hits to return (default 5)"}},"required":["query"],"additionalProperties":true}"#;
 const SCHEMA_ASSET_UPLOAD: &str = r#"{"type":"object","properties":{"assets_dir_path":{"type":"string","description":"Target assets dir (e.g. /assets/)"},"files":{"type":"array","items":{"type":"string"},"description":"Local file paths"}},"required":["files"],"additionalProperties":true}"#;
 
 const TOOL_SPECS: &[ToolSpec] = &[
     ToolSpec {
-        name: "siyuan_notebook_ls",
-        endpoint: "/api/notebook/lsNotebooks",
+        name: Cow::Borrowed("siyuan_notebook_ls"),
+        endpoint: Cow::Borrowed("/api/notebook/lsNotebooks"),
         kind: ToolKind::Json,
-        description: "List notebooks. No parameters. Use to obtain notebook IDs.",
-        schema: SCHEMA_EMPTY,
+        description: Cow::Borrowed("List notebooks. No parameters. Use to obtain notebook IDs."),
+        schema: Cow::Borrowed(SCHEMA_EMPTY),
     },
     ToolSpec {
-        name: "siyuan_notebook_open",
-        endpoint: "/api/notebook/openNotebook",
+        name: Cow::Borrowed("siyuan_notebook_open"),
+        endpoint: Cow::Borrowed("/api/notebook/openNotebook"),
         kind: ToolKind::Json,
-        description: "Open a notebook by ID.",
-        schema: SCHEMA_NOTEBOOK_ID,
+        description: Cow::Borrowed("Open a notebook by ID."),
+        schema: Cow::Borrowed(SCHEMA_NOTEBOOK_ID),
     },
     ToolSpec {
-        name: "siyuan_notebook_close",
-        endpoint: "/api/notebook/closeNotebook",
+        name: Cow::Borrowed("siyuan_notebook_close"),
+        endpoint: Cow::Borrowed("/api/notebook/closeNotebook"),
         kind: ToolKind::Json,
-        description: "Close a notebook by ID.",
-        schema: SCHEMA_NOTEBOOK_ID,
+        description: Cow::Borrowed("Close a notebook by ID."),
+        schema: Cow::Borrowed(SCHEMA_NOTEBOOK_ID),
     },
     ToolSpec {
-        name: "siyuan_notebook_rename",
-        endpoint: "/api/notebook/renameNotebook",
+        name: Cow::Borrowed("siyuan_notebook_rename"),
+        endpoint: Cow::Borrowed("/api/notebook/renameNotebook"),
         kind: ToolKind::Json,
-        description: "Rename a notebook by ID.",
-        schema: SCHEMA_NOTEBOOK_ID_NAME,
+        description: Cow::Borrowed("Rename a notebook by ID."),
+        schema: Cow::Borrowed(SCHEMA_NOTEBOOK_ID_NAME),
     },
     ToolSpec {
-        name: "siyuan_notebook_create",
-        endpoint: "/api/notebook/createNotebook",
+        name: Cow::Borrowed("siyuan_notebook_create"),
+        endpoint: Cow::Borrowed("/api/notebook/createNotebook"),
         kind: ToolKind::Json,
-        description: "Create a new notebook.",
-        schema: SCHEMA_NOTEBOOK_CREATE,
+        description: Cow::Borrowed("Create a new notebook."),
+        schema: Cow::Borrowed(SCHEMA_NOTEBOOK_CREATE),
     },
     ToolSpec {
-        name: "siyuan_notebook_remove",
-        endpoint: "/api/notebook/removeNotebook",
+        name: Cow::Borrowed("siyuan_notebook_remove"),
+        endpoint: Cow::Borrowed("/api/notebook/removeNotebook"),
         kind: ToolKind::Json,
-        description: "Remove a notebook by ID.",
-        schema: SCHEMA_NOTEBOOK_ID,
+        description: Cow::Borrowed("Remove a notebook by ID."),
+        schema: Cow::Borrowed(SCHEMA_NOTEBOOK_ID),
     },
     ToolSpec {
-        name: "siyuan_notebook_get_conf",
-        endpoint: "/api/notebook/getNotebookConf",
+        name: Cow::Borrowed("siyuan_notebook_get_conf"),
+        endpoint: Cow::Borrowed("/api/notebook/getNotebookConf"),
         kind: ToolKind::Json,
-        description: "Fetch notebook configuration by ID.",
-        schema: SCHEMA_NOTEBOOK_ID,
+        description: Cow::Borrowed("Fetch notebook configuration by ID."),
+        schema: Cow::Borrowed(SCHEMA_NOTEBOOK_ID),
     },
     ToolSpec {
-        name: "siyuan_notebook_set_conf",
-        endpoint: "/api/notebook/setNotebookConf",
+        name: Cow::Borrowed("siyuan_notebook_set_conf"),
+        endpoint: Cow::Borrowed("/api/notebook/setNotebookConf"),
         kind: ToolKind::Json,
-        description: "Save notebook configuration by ID.",
-        schema: SCHEMA_NOTEBOOK_CONF,
+        description: Cow::Borrowed("Save notebook configuration by ID."),
+        schema: Cow::Borrowed(SCHEMA_NOTEBOOK_CONF),
     },
     ToolSpec {
-        name: "siyuan_doc_create_md",
-        endpoint: "/api/filetree/createDocWithMd",
+        name: Cow::Borrowed("siyuan_doc_create_md"),
+        endpoint: Cow::Borrowed("/api/filetree/createDocWithMd"),
         kind: ToolKind::Json,
-        description: "Create a document with Markdown content.",
-        schema: SCHEMA_DOC_CREATE_MD,
+        description: Cow::Borrowed("Create a document with Markdown content."),
+        schema: Cow::Borrowed(SCHEMA_DOC_CREATE_MD),
     },
     ToolSpec {
-        name: "siyuan_doc_rename",
-        endpoint: "/api/filetree/renameDoc",
+        name: Cow::Borrowed("siyuan_doc_rename"),
+        endpoint: Cow::Borrowed("/api/filetree/renameDoc"),
         kind: ToolKind::Json,
-        description: "Rename a document by notebook + path.",
-        schema: SCHEMA_DOC_RENAME,
+        description: Cow::Borrowed("Rename a document by notebook + path."),
+        schema: Cow::Borrowed(SCHEMA_DOC_RENAME),
     },
     ToolSpec {
-        name: "siyuan_doc_rename_by_id",
-        endpoint: "/api/filetree/renameDocByID",
+        name: Cow::Borrowed("siyuan_doc_rename_by_id"),
+        endpoint: Cow::Borrowed("/api/filetree/renameDocByID"),
         kind: ToolKind::Json,
-        description: "Rename a document by ID.",
-        schema: SCHEMA_DOC_RENAME_BY_ID,
+        description: Cow::Borrowed("Rename a document by ID."),
+        schema: Cow::Borrowed(SCHEMA_DOC_RENAME_BY_ID),
     },
     ToolSpec {
-        name: "siyuan_doc_remove",
-        endpoint: "/api/filetree/removeDoc",
+        name: Cow::Borrowed("siyuan_doc_remove"),
+        endpoint: Cow::Borrowed("/api/filetree/removeDoc"),
         kind: ToolKind::Json,
-        description: "Remove a document by notebook + path.",
-        schema: SCHEMA_DOC_REMOVE,
+        description: Cow::Borrowed("Remove a document by notebook + path."),
+        schema: Cow::Borrowed(SCHEMA_DOC_REMOVE),
     },
     ToolSpec {
-        name: "siyuan_doc_remove_by_id",
-        endpoint: "/api/filetree/removeDocByID",
+        name: Cow::Borrowed("siyuan_doc_remove_by_id"),
+        endpoint: Cow::Borrowed("/api/filetree/removeDocByID"),
         kind: ToolKind::Json,
-        description: "Remove a document by ID.",
-        schema: SCHEMA_ID_ONLY,
+        description: Cow::Borrowed("Remove a document by ID."),
+        schema: Cow::Borrowed(SCHEMA_ID_ONLY),
     },
     ToolSpec {
-        name: "siyuan_doc_move",
-        endpoint: "/api/filetree/moveDocs",
+        name: Cow::Borrowed("siyuan_doc_move"),
+        endpoint: Cow::Borrowed("/api/filetree/moveDocs"),
         kind: ToolKind::Json,
-        description: "Move documents by source paths to a target notebook/path.",
-        schema: SCHEMA_DOC_MOVE,
+        description: Cow::Borrowed("Move documents by source paths to a target notebook/path."),
+        schema: Cow::Borrowed(SCHEMA_DOC_MOVE),
     },
     ToolSpec {
-        name: "siyuan_doc_move_by_id",
-        endpoint: "/api/filetree/moveDocsByID",
+        name: Cow::Borrowed("siyuan_doc_move_by_id"),
+        endpoint: Cow::Borrowed("/api/filetree/moveDocsByID"),
         kind: ToolKind::Json,
-        description: "Move documents by IDs to a target parent ID or notebook ID.",
-        schema: SCHEMA_DOC_MOVE_BY_ID,
+        description: Cow::Borrowed("Move documents by IDs to a target parent ID or notebook ID."),
+        schema: Cow::Borrowed(SCHEMA_DOC_MOVE_BY_ID),
     },
     ToolSpec {
-        name: "siyuan_doc_get_hpath_by_path",
-        endpoint: "/api/filetree/getHPathByPath",
+        name: Cow::Borrowed("siyuan_doc_get_hpath_by_path"),
+        endpoint: Cow::Borrowed("/api/filetree/getHPathByPath"),
         kind: ToolKind::Json,
-        description: "Get human-readable path from notebook + storage path.",
-        schema: SCHEMA_GET_HPATH_BY_PATH,
+        description: Cow::Borrowed("Get human-readable path from notebook + storage path."),
+        schema: Cow::Borrowed(SCHEMA_GET_HPATH_BY_PATH),
     },
     ToolSpec {
-        name: "siyuan_doc_get_hpath_by_id",
-        endpoint: "/api/filetree/getHPathByID",
+        name: Cow::Borrowed("siyuan_doc_get_hpath_by_id"),
+        endpoint: Cow::Borrowed("/api/filetree/getHPathByID"),
         kind: ToolKind::Json,
-        description: "Get human-readable path from block/document ID.",
-        schema: SCHEMA_ID_ONLY,
+        description: Cow::Borrowed("Get human-readable path from block/document ID."),
+        schema: Cow::Borrowed(SCHEMA_ID_ONLY),
     },
     ToolSpec {
-        name: "siyuan_doc_get_path_by_id",
-        endpoint: "/api/filetree/getPathByID",
+        name: Cow::Borrowed("siyuan_doc_get_path_by_id"),
+        endpoint: Cow::Borrowed("/api/filetree/getPathByID"),
         kind: ToolKind::Json,
-        description: "Get storage path and notebook ID from block/document ID.",
-        schema: SCHEMA_ID_ONLY,
+        description: Cow::Borrowed("Get storage path and notebook ID from block/document ID."),
+        schema: Cow::Borrowed(SCHEMA_ID_ONLY),
     },
     ToolSpec {
-        name: "siyuan_doc_get_ids_by_hpath",
-        endpoint: "/api/filetree/getIDsByHPath",
+        name: Cow::Borrowed("siyuan_doc_get_ids_by_hpath"),
+        endpoint: Cow::Borrowed("/api/filetree/getIDsByHPath"),
         kind: ToolKind::Json,
-        description: "Get IDs from human-readable path + notebook ID.",
-        schema: SCHEMA_GET_IDS_BY_HPATH,
+        description: Cow::Borrowed("Get IDs from human-readable path + notebook ID."),
+        schema: Cow::Borrowed(SCHEMA_GET_IDS_BY_HPATH),
     },
     ToolSpec {
-        name: "siyuan_asset_upload",
-        endpoint: "/api/asset/upload",
+        name: Cow::Borrowed("siyuan_asset_upload"),
+        endpoint: Cow::Borrowed("/api/asset/upload"),
         kind: ToolKind::AssetUpload,
-        description: "Upload assets from local files. Uses multipart. Params: assets_dir_path, files[].",
-        schema: SCHEMA_ASSET_UPLOAD,
+        description: Cow::Borrowed("Upload assets from local files. Uses multipart. Params: assets_dir_path, files[]."),
+        schema: Cow::Borrowed(SCHEMA_ASSET_UPLOAD),
     },
     ToolSpec {
-        name: "siyuan_block_insert",
-        endpoint: "/api/block/insertBlock",
+        name: Cow::Borrowed("siyuan_block_insert"),
+        endpoint: Cow::Borrowed("/api/block/insertBlock"),
         kind: ToolKind::Json,
-        description: "Insert blocks using nextID/previousID/parentID anchors.",
-        schema: SCHEMA_BLOCK_INSERT,
+        description: Cow::Borrowed("Insert blocks using nextID/previousID/parentID anchors."),
+        schema: Cow::Borrowed(SCHEMA_BLOCK_INSERT),
     },
     ToolSpec {
-        name: "siyuan_block_prepend",
-        endpoint: "/api/block/prependBlock",
+        name: Cow::Borrowed("siyuan_block_prepend"),
+        endpoint: Cow::Borrowed("/api/block/prependBlock"),
         kind: ToolKind::Json,
-        description: "Prepend blocks to parentID.",
-        schema: SCHEMA_BLOCK_PREPEND,
+        description: Cow::Borrowed("Prepend blocks to parentID."),
+        schema: Cow::Borrowed(SCHEMA_BLOCK_PREPEND),
     },
     ToolSpec {
-        name: "siyuan_block_append",
-        endpoint: "/api/block/appendBlock",
+        name: Cow::Borrowed("siyuan_block_append"),
+        endpoint: Cow::Borrowed("/api/block/appendBlock"),
         kind: ToolKind::Json,
-        description: "Append blocks to parentID.",
-        schema: SCHEMA_BLOCK_PREPEND,
+        description: Cow::Borrowed("Append blocks to parentID."),
+        schema: Cow::Borrowed(SCHEMA_BLOCK_PREPEND),
     },
     ToolSpec {
-        name: "siyuan_block_update",
-        endpoint: "/api/block/updateBlock",
+        name: Cow::Borrowed("siyuan_block_update"),
+        endpoint: Cow::Borrowed("/api/block/updateBlock"),
         kind: ToolKind::Json,
-        description: "Update a block by ID.",
-        schema: SCHEMA_BLOCK_UPDATE,
+        description: Cow::Borrowed("Update a block by ID."),
+        schema: Cow::Borrowed(SCHEMA_BLOCK_UPDATE),
     },
     ToolSpec {
-        name: "siyuan_block_delete",
-        endpoint: "/api/block/deleteBlock",
+        name: Cow::Borrowed("siyuan_block_delete"),
+        endpoint: Cow::Borrowed("/api/block/deleteBlock"),
         kind: ToolKind::Json,
-        description: "Delete a block by ID.",
-        schema: SCHEMA_ID_ONLY,
+        description: Cow::Borrowed("Delete a block by ID."),
+        schema: Cow::Borrowed(SCHEMA_ID_ONLY),
     },
     ToolSpec {
-        name: "siyuan_block_move",
-        endpoint: "/api/block/moveBlock",
+        name: Cow::Borrowed("siyuan_block_move"),
+        endpoint: Cow::Borrowed("/api/block/moveBlock"),
         kind: ToolKind::Json,
-        description: "Move a block with previousID/parentID anchors.",
-        schema: SCHEMA_BLOCK_MOVE,
+        description: Cow::Borrowed("Move a block with previousID/parentID anchors."),
+        schema: Cow::Borrowed(SCHEMA_BLOCK_MOVE),
+    },
+    ToolSpec {
+        name: Cow::Borrowed("siyuan_block_batch"),
+        endpoint: Cow::Borrowed("/api/block/insertBlock"),
+        kind: ToolKind::Batch,
+        description: Cow::Borrowed("Apply an ordered array of block ops (insert/update/delete/move) in one call."),
+        schema: Cow::Borrowed(SCHEMA_BLOCK_BATCH),
     },
     ToolSpec {
-        name: "siyuan_block_fold",
-        endpoint: "/api/block/foldBlock",
+        name: Cow::Borrowed("siyuan_block_fold"),
+        endpoint: Cow::Borrowed("/api/block/foldBlock"),
         kind: ToolKind::Json,
-        description: "Fold a block by ID.",
-        schema: SCHEMA_ID_ONLY,
+        description: Cow::Borrowed("Fold a block by ID."),
+        schema: Cow::Borrowed(SCHEMA_ID_ONLY),
     },
     ToolSpec {
-        name: "siyuan_block_unfold",
-        endpoint: "/api/block/unfoldBlock",
+        name: Cow::Borrowed("siyuan_block_unfold"),
+        endpoint: Cow::Borrowed("/api/block/unfoldBlock"),
         kind: ToolKind::Json,
-        description: "Unfold a block by ID.",
-        schema: SCHEMA_ID_ONLY,
+        description: Cow::Borrowed("Unfold a block by ID."),
+        schema: Cow::Borrowed(SCHEMA_ID_ONLY),
     },
     ToolSpec {
-        name: "siyuan_block_get_kramdown",
-        endpoint: "/api/block/getBlockKramdown",
+        name: Cow::Borrowed("siyuan_block_get_kramdown"),
+        endpoint: Cow::Borrowed("/api/block/getBlockKramdown"),
         kind: ToolKind::Json,
-        description: "Get block kramdown by ID.",
-        schema: SCHEMA_ID_ONLY,
+        description: Cow::Borrowed("Get block kramdown by ID."),
+        schema: Cow::Borrowed(SCHEMA_ID_ONLY),
     },
     ToolSpec {
-        name: "siyuan_block_get_children",
-        endpoint: "/api/block/getChildBlocks",
+        name: Cow::Borrowed("siyuan_block_get_children"),
+        endpoint: Cow::Borrowed("/api/block/getChildBlocks"),
         kind: ToolKind::Json,
-        description: "List child blocks by parent ID.",
-        schema: SCHEMA_ID_ONLY,
+        description: Cow::Borrowed("List child blocks by parent ID."),
+        schema: Cow::Borrowed(SCHEMA_ID_ONLY),
     },
     ToolSpec {
-        name: "siyuan_block_transfer_ref",
-        endpoint: "/api/block/transferBlockRef",
+        name: Cow::Borrowed("siyuan_block_transfer_ref"),
+        endpoint: Cow::Borrowed("/api/block/transferBlockRef"),
         kind: ToolKind::Json,
-        description: "Transfer block references from one def block to another.",
-        schema: SCHEMA_BLOCK_TRANSFER_REF,
+        description: Cow::Borrowed("Transfer block references from one def block to another."),
+        schema: Cow::Borrowed(SCHEMA_BLOCK_TRANSFER_REF),
     },
     ToolSpec {
-        name: "siyuan_attr_set",
-        endpoint: "/api/attr/setBlockAttrs",
+        name: Cow::Borrowed("siyuan_attr_set"),
+        endpoint: Cow::Borrowed("/api/attr/setBlockAttrs"),
         kind: ToolKind::Json,
-        description: "Set block attributes.",
-        schema: SCHEMA_ATTR_SET,
+        description: Cow::Borrowed("Set block attributes."),
+        schema: Cow::Borrowed(SCHEMA_ATTR_SET),
     },
     ToolSpec {
-        name: "siyuan_attr_get",
-        endpoint: "/api/attr/getBlockAttrs",
+        name: Cow::Borrowed("siyuan_attr_get"),
+        endpoint: Cow::Borrowed("/api/attr/getBlockAttrs"),
         kind: ToolKind::Json,
-        description: "Get block attributes by ID.",
-        schema: SCHEMA_ID_ONLY,
+        description: Cow::Borrowed("Get block attributes by ID."),
+        schema: Cow::Borrowed(SCHEMA_ID_ONLY),
+    },
+    ToolSpec {
+        name: Cow::Borrowed("siyuan_search"),
+        endpoint: Cow::Borrowed("/api/query/sql"),
+        kind: ToolKind::Search,
+        description: Cow::Borrowed("Full-text search over blocks, re-ranked locally with BM25."),
+        schema: Cow::Borrowed(SCHEMA_SEARCH),
     },
     ToolSpec {
-        name: "siyuan_sql_query",
-        endpoint: "/api/query/sql",
+        name: Cow::Borrowed("siyuan_sql_query"),
+        endpoint: Cow::Borrowed("/api/query/sql"),
         kind: ToolKind::Json,
-        description: "Execute SQL query against SiYuan database.",
-        schema: SCHEMA_SQL_QUERY,
+        description: Cow::Borrowed("Execute SQL query against SiYuan database."),
+        schema: Cow::Borrowed(SCHEMA_SQL_QUERY),
     },
     ToolSpec {
-        name: "siyuan_sql_flush",
-        endpoint: "/api/sqlite/flushTransaction",
+        name: Cow::Borrowed("siyuan_sql_flush"),
+        endpoint: Cow::Borrowed("/api/sqlite/flushTransaction"),
         kind: ToolKind::Json,
-        description: "Flush the current SQLite transaction. No parameters.",
-        schema: SCHEMA_EMPTY,
+        description: Cow::Borrowed("Flush the current SQLite transaction. No parameters."),
+        schema: Cow::Borrowed(SCHEMA_EMPTY),
     },
     ToolSpec {
-        name: "siyuan_template_render",
-        endpoint: "/api/template/render",
+        name: Cow::Borrowed("siyuan_template_render"),
+        endpoint: Cow::Borrowed("/api/template/render"),
         kind: ToolKind::Json,
-        description: "Render a template file for a document.",
-        schema: SCHEMA_TEMPLATE_RENDER,
+        description: Cow::Borrowed("Render a template file for a document."),
+        schema: Cow::Borrowed(SCHEMA_TEMPLATE_RENDER),
     },
     ToolSpec {
-        name: "siyuan_template_render_sprig",
-        endpoint: "/api/template/renderSprig",
+        name: Cow::Borrowed("siyuan_template_render_sprig"),
+        endpoint: Cow::Borrowed("/api/template/renderSprig"),
         kind: ToolKind::Json,
-        description: "Render a Sprig template string.",
-        schema: SCHEMA_TEMPLATE_RENDER_SPRIG,
+        description: Cow::Borrowed("Render a Sprig template string."),
+        schema: Cow::Borrowed(SCHEMA_TEMPLATE_RENDER_SPRIG),
     },
     ToolSpec {
-        name: "siyuan_file_get",
-        endpoint: "/api/file/getFile",
+        name: Cow::Borrowed("siyuan_file_get"),
+        endpoint: Cow::Borrowed("/api/file/getFile"),
         kind: ToolKind::GetFile,
-        description: "Download a file. Returns body_base64 + content_type.",
-        schema: SCHEMA_FILE_PATH,
+        description: Cow::Borrowed("Download a file. Returns body_base64 + content_type."),
+        schema: Cow::Borrowed(SCHEMA_FILE_PATH),
     },
     ToolSpec {
-        name: "siyuan_file_put",
-        endpoint: "/api/file/putFile",
+        name: Cow::Borrowed("siyuan_file_put"),
+        endpoint: Cow::Borrowed("/api/file/putFile"),
         kind: ToolKind::PutFile,
-        description: "Upload a file or create a directory (multipart). Params: path, is_dir, mod_time, file_path.",
-        schema: SCHEMA_FILE_PUT,
+        description: Cow::Borrowed("Upload a file or create a directory (multipart). Params: path, is_dir, mod_time, file_path."),
+        schema: Cow::Borrowed(SCHEMA_FILE_PUT),
     },
     ToolSpec {
-        name: "siyuan_file_remove",
-        endpoint: "/api/file/removeFile",
+        name: Cow::Borrowed("siyuan_file_remove"),
+        endpoint: Cow::Borrowed("/api/file/removeFile"),
         kind: ToolKind::Json,
-        description: "Remove a file by workspace path.",
-        schema: SCHEMA_FILE_PATH,
+        description: Cow::Borrowed("Remove a file by workspace path."),
+        schema: Cow::Borrowed(SCHEMA_FILE_PATH),
     },
     ToolSpec {
-        name: "siyuan_file_rename",
-        endpoint: "/api/file/renameFile",
+        name: Cow::Borrowed("siyuan_file_rename"),
+        endpoint: Cow::Borrowed("/api/file/renameFile"),
         kind: ToolKind::Json,
-        description: "Rename a file by workspace path.",
-        schema: SCHEMA_FILE_RENAME,
+        description: Cow::Borrowed("Rename a file by workspace path."),
+        schema: Cow::Borrowed(SCHEMA_FILE_RENAME),
     },
     ToolSpec {
-        name: "siyuan_file_read_dir",
-        endpoint: "/api/file/readDir",
+        name: Cow::Borrowed("siyuan_file_read_dir"),
+        endpoint: Cow::Borrowed("/api/file/readDir"),
         kind: ToolKind::Json,
-        description: "List files in a directory by workspace path.",
-        schema: SCHEMA_FILE_READ_DIR,
+        description: Cow::Borrowed("List files in a directory by workspace path."),
+        schema: Cow::Borrowed(SCHEMA_FILE_READ_DIR),
     },
     ToolSpec {
-        name: "siyuan_export_md",
-        endpoint: "/api/export/exportMdContent",
+        name: Cow::Borrowed("siyuan_export_md"),
+        endpoint: Cow::Borrowed("/api/export/exportMdContent"),
         kind: ToolKind::Json,
-        description: "Export a document as Markdown content by ID.",
-        schema: SCHEMA_EXPORT_MD,
+        description: Cow::Borrowed("Export a document as Markdown content by ID."),
+        schema: Cow::Borrowed(SCHEMA_EXPORT_MD),
+    },
+    ToolSpec {
+        name: Cow::Borrowed("siyuan_doc_export_pod"),
+        endpoint: Cow::Borrowed("/api/export/exportMdContent"),
+        kind: ToolKind::ExportPod,
+        description: Cow::Borrowed("Export a document's Markdown plus every referenced asset as a single self-contained zip."),
+        schema: Cow::Borrowed(SCHEMA_EXPORT_MD),
     },
     ToolSpec {
-        name: "siyuan_export_resources",
-        endpoint: "/api/export/exportResources",
+        name: Cow::Borrowed("siyuan_export_resources"),
+        endpoint: Cow::Borrowed("/api/export/exportResources"),
         kind: ToolKind::Json,
-        description: "Export files/folders to a zip; returns zip path.",
-        schema: SCHEMA_EXPORT_RESOURCES,
+        description: Cow::Borrowed("Export files/folders to a zip; returns zip path."),
+        schema: Cow::Borrowed(SCHEMA_EXPORT_RESOURCES),
     },
     ToolSpec {
-        name: "siyuan_convert_pandoc",
-        endpoint: "/api/convert/pandoc",
+        name: Cow::Borrowed("siyuan_convert_pandoc"),
+        endpoint: Cow::Borrowed("/api/convert/pandoc"),
         kind: ToolKind::Json,
-        description: "Run pandoc conversion in a temp directory.",
-        schema: SCHEMA_PANDOC,
+        description: Cow::Borrowed("Run pandoc conversion in a temp directory."),
+        schema: Cow::Borrowed(SCHEMA_PANDOC),
     },
     ToolSpec {
-        name: "siyuan_notify_msg",
-        endpoint: "/api/notification/pushMsg",
+        name: Cow::Borrowed("siyuan_notify_msg"),
+        endpoint: Cow::Borrowed("/api/notification/pushMsg"),
         kind: ToolKind::Json,
-        description: "Push a normal notification message.",
-        schema: SCHEMA_NOTIFY,
+        description: Cow::Borrowed("Push a normal notification message."),
+        schema: Cow::Borrowed(SCHEMA_NOTIFY),
     },
     ToolSpec {
-        name: "siyuan_notify_err",
-        endpoint: "/api/notification/pushErrMsg",
+        name: Cow::Borrowed("siyuan_notify_err"),
+        endpoint: Cow::Borrowed("/api/notification/pushErrMsg"),
         kind: ToolKind::Json,
-        description: "Push an error notification message.",
-        schema: SCHEMA_NOTIFY,
+        description: Cow::Borrowed("Push an error notification message."),
+        schema: Cow::Borrowed(SCHEMA_NOTIFY),
     },
     ToolSpec {
-        name: "siyuan_network_forward_proxy",
-        endpoint: "/api/network/forwardProxy",
+        name: Cow::Borrowed("siyuan_network_forward_proxy"),
+        endpoint: Cow::Borrowed("/api/network/forwardProxy"),
         kind: ToolKind::Json,
-        description: "Forward proxy HTTP request through SiYuan.",
-        schema: SCHEMA_NETWORK_FORWARD_PROXY,
+        description: Cow::Borrowed("Forward proxy HTTP request through SiYuan."),
+        schema: Cow::Borrowed(SCHEMA_NETWORK_FORWARD_PROXY),
     },
     ToolSpec {
-        name: "siyuan_system_boot_progress",
-        endpoint: "/api/system/bootProgress",
+        name: Cow::Borrowed("siyuan_system_boot_progress"),
+        endpoint: Cow::Borrowed("/api/system/bootProgress"),
         kind: ToolKind::Json,
-        description: "Get system boot progress. No parameters.",
-        schema: SCHEMA_EMPTY,
+        description: Cow::Borrowed("Get system boot progress. No parameters."),
+        schema: Cow::Borrowed(SCHEMA_EMPTY),
     },
     ToolSpec {
-        name: "siyuan_system_version",
-        endpoint: "/api/system/version",
+        name: Cow::Borrowed("siyuan_system_version"),
+        endpoint: Cow::Borrowed("/api/system/version"),
         kind: ToolKind::Json,
-        description: "Get system version. No parameters.",
-        schema: SCHEMA_EMPTY,
+        description: Cow::Borrowed("Get system version. No parameters."),
+        schema: Cow::Borrowed(SCHEMA_EMPTY),
     },
     ToolSpec {
-        name: "siyuan_system_current_time",
-        endpoint: "/api/system/currentTime",
+        name: Cow::Borrowed("siyuan_system_current_time"),
+        endpoint: Cow::Borrowed("/api/system/currentTime"),
         kind: ToolKind::Json,
-        description: "Get system current time (ms). No parameters.",
-        schema: SCHEMA_EMPTY,
+        description: Cow::Borrowed("Get system current time (ms). No parameters."),
+        schema: Cow::Borrowed(SCHEMA_EMPTY),
+    },
+    ToolSpec {
+        name: Cow::Borrowed("siyuan_index_build"),
+        endpoint: Cow::Borrowed("/api/query/sql"),
+        kind: ToolKind::IndexBuild,
+        description: Cow::Borrowed("Build or refresh the local semantic embedding index over blocks, re-embedding only new or changed content."),
+        schema: Cow::Borrowed(SCHEMA_INDEX_BUILD),
+    },
+    ToolSpec {
+        name: Cow::Borrowed("siyuan_semantic_search"),
+        endpoint: Cow::Borrowed("n/a"),
+        kind: ToolKind::SemanticSearch,
+        description: Cow::Borrowed("Search the local semantic embedding index by meaning and return the closest matching block windows."),
+        schema: Cow::Borrowed(SCHEMA_SEMANTIC_SEARCH),
     },
 ];
 
+const SCHEMA_JOB_SUBMIT: &str = r#"{"type":"object","properties":{"tool_name":{"type":"string","description":"Name of an existing tool to run as a background job"},"args":{"type":"object","description":"Arguments to pass to the wrapped tool"}},"required":["tool_name"],"additionalProperties":true}"#;
+const SCHEMA_JOB_ID: &str = r#"{"type":"object","properties":{"job_id":{"type":"string","description":"Job ID"}},"required":["job_id"],"additionalProperties":true}"#;
+
+const SCHEMA_WATCH_SUBSCRIBE: &str = r#"{"type":"object","properties":{"predicate":{"type":"string","description":"Optional SQL WHERE-clause fragment scoping which blocks are watched"}},"additionalProperties":true}"#;
+
+const WATCH_TOOL_SPECS: &[(&str, &str, &str)] = &[(
+    "siyuan_watch_subscribe",
+    "Subscribe to SiYuan block changes; emits resource-updated notifications as blocks are created or updated, and a resource-list-changed notification when blocks are deleted.",
+    SCHEMA_WATCH_SUBSCRIBE,
+)];
+
+const JOB_TOOL_SPECS: &[(&str, &str, &str)] = &[
+    (
+        "siyuan_job_submit",
+        "Submit any existing tool call as a background job; returns a job ID immediately.",
+        SCHEMA_JOB_SUBMIT,
+    ),
+    (
+        "siyuan_job_status",
+        "Get a background job's state, progress, and result if completed.",
+        SCHEMA_JOB_ID,
+    ),
+    (
+        "siyuan_job_cancel",
+        "Cancel a queued or running background job.",
+        SCHEMA_JOB_ID,
+    ),
+];
+
 #[derive(Clone)]
 struct SiyuanServer {
+    client: Arc<SiyuanClient>,
     tools: Arc<Vec<Tool>>,
-    tool_handlers: Arc<HashMap<&'static str, SiyuanTool>>,
+    tool_handlers: Arc<HashMap<Cow<'static, str>, SiyuanTool>>,
+    jobs: Arc<jobs::JobManager>,
+    watch: Arc<watch::WatchManager>,
+    metrics: Option<Arc<metrics::Metrics>>,
 }
 
 impl SiyuanServer {
-    fn new(client: Arc<SiyuanClient>) -> Self {
+    fn new(
+        client: Arc<SiyuanClient>,
+        default_dry_run: bool,
+        tool_specs: &[ToolSpec],
+        jobs_state_file: std::path::PathBuf,
+        metrics: Option<Arc<metrics::Metrics>>,
+        semantic_index: Arc<semantic::SemanticIndex>,
+        watch: Arc<watch::WatchManager>,
+    ) -> Self {
         let mut tools = Vec::new();
         let mut handlers = HashMap::new();
-        for spec in TOOL_SPECS {
-            let handler = SiyuanTool::new(client.clone(), spec);
-            let schema = parse_schema(spec.schema);
-            let tool = Tool::new(spec.name, spec.description, Arc::new(schema));
+        for spec in tool_specs {
+            let schema = with_dry_run_property(parse_schema(&spec.schema));
+            let handler = SiyuanTool::new(
+                client.clone(),
+                spec,
+                &schema,
+                default_dry_run,
+                semantic_index.clone(),
+            );
+            let tool = Tool::new(spec.name.clone(), spec.description.clone(), Arc::new(schema));
             tools.push(tool);
-            handlers.insert(spec.name, handler);
+            handlers.insert(spec.name.clone(), handler);
+        }
+        for &(name, description, schema) in JOB_TOOL_SPECS {
+            let schema = parse_schema(schema);
+            tools.push(Tool::new(name, description, Arc::new(schema)));
+        }
+        for &(name, description, schema) in WATCH_TOOL_SPECS {
+            let schema = parse_schema(schema);
+            tools.push(Tool::new(name, description, Arc::new(schema)));
         }
         debug!("registered {} tools", tools.len());
         Self {
+            client,
             tools: Arc::new(tools),
             tool_handlers: Arc::new(handlers),
+            jobs: jobs::JobManager::load(jobs_state_file),
+            watch,
+            metrics,
         }
     }
 
-    async fn handle_tool_call(&self, name: &str, args: Value) -> Result<Value, McpError> {
+    async fn dispatch_tool(&self, name: &str, args: Value) -> Result<Value, McpError> {
         let handler = self.tool_handlers.get(name).ok_or_else(|| {
             McpError::invalid_params(format!("unknown tool: {}", name), None)
         })?;
         handler.handle(args).await
     }
+
+    async fn handle_tool_call(
+        &self,
+        name: &str,
+        args: Value,
+        peer: Peer<RoleServer>,
+    ) -> Result<Value, McpError> {
+        let started_at = std::time::Instant::now();
+        let result = match name {
+            "siyuan_job_submit" => jobs::handle_submit(self, args).await,
+            "siyuan_job_status" => jobs::handle_status(self, args).await,
+            "siyuan_job_cancel" => jobs::handle_cancel(self, args).await,
+            "siyuan_watch_subscribe" => {
+                watch::handle_subscribe(self.client.clone(), &self.watch, peer, args).await
+            }
+            _ => self.dispatch_tool(name, args).await,
+        };
+        if let Some(metrics) = &self.metrics {
+            metrics.record_invocation(name, started_at.elapsed(), &result);
+        }
+        result
+    }
 }
 
 impl ServerHandler for SiyuanServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .build(),
             server_info: Implementation {
                 name: "siyuan-mcp".to_string(),
                 version: env!("CARGO_PKG_VERSION").to_string(),
@@ -764,7 +1083,7 @@ impl ServerHandler for SiyuanServer {
     fn call_tool(
         &self,
         request: CallToolRequestParam,
-        _context: RequestContext<RoleServer>,
+        context: RequestContext<RoleServer>,
     ) -> impl std::future::Future<Output = Result<CallToolResult, McpError>> + Send + '_ {
         let server = self.clone();
         async move {
@@ -773,7 +1092,9 @@ impl ServerHandler for SiyuanServer {
                 .arguments
                 .map(Value::Object)
                 .unwrap_or(Value::Null);
-            let result = server.handle_tool_call(name, args).await?;
+            let result = server
+                .handle_tool_call(name, args, context.peer.clone())
+                .await?;
             let content = Content::json(result)?;
             Ok(CallToolResult::success(vec![content]))
         }
@@ -794,15 +1115,78 @@ async fn main() -> anyhow::Result<()> {
         args.timeout_ms,
         args.token.is_some()
     );
+    let response_cache: Arc<dyn cache::ResponseCache> = match args.cache {
+        CacheBackendArg::None => Arc::new(cache::NoCache),
+        CacheBackendArg::Memory => Arc::new(cache::MemoryCache::new(1024, args.cache_ttl_ms)),
+        CacheBackendArg::Fs => {
+            let dir = args
+                .cache_dir
+                .clone()
+                .unwrap_or_else(|| std::path::PathBuf::from(".siyuan-mcp-cache"));
+            Arc::new(cache::FsCache::new(dir, args.cache_ttl_ms)?)
+        }
+    };
+    let server_metrics = args.metrics_addr.as_ref().map(|_| metrics::Metrics::new());
     let client = Arc::new(SiyuanClient::new(
         args.base_url,
         args.token,
         args.timeout_ms,
+        response_cache,
+        server_metrics.clone(),
     )?);
 
-    let server = SiyuanServer::new(client);
-    let running = server.serve(stdio()).await?;
-    running.waiting().await?;
+    let tool_specs = match &args.tools_file {
+        Some(path) => tools_manifest::load_and_merge(TOOL_SPECS, path)?,
+        None => TOOL_SPECS.to_vec(),
+    };
+    let embedder: Arc<dyn semantic::Embedder> = Arc::new(semantic::HttpEmbedder::new(
+        args.embeddings_endpoint,
+        args.embeddings_model,
+    ));
+    let semantic_index = Arc::new(semantic::SemanticIndex::new(
+        args.semantic_index_file,
+        embedder,
+    ));
+    let watch_manager = watch::WatchManager::load(args.watch_state_file, args.watch_interval_ms);
+    let server = SiyuanServer::new(
+        client,
+        args.default_dry_run,
+        &tool_specs,
+        args.jobs_state_file,
+        server_metrics.clone(),
+        semantic_index,
+        watch_manager,
+    );
+    jobs::resume_pending(&server).await;
+
+    if let (Some(addr), Some(metrics)) = (args.metrics_addr.as_ref(), server_metrics) {
+        let bind: std::net::SocketAddr = addr
+            .parse()
+            .with_context(|| format!("invalid --metrics-addr `{}`", addr))?;
+        tokio::spawn(metrics::serve(metrics, bind));
+    }
+
+    match args.transport {
+        TransportKind::Stdio => {
+            let running = server.serve(stdio()).await?;
+            running.waiting().await?;
+        }
+        TransportKind::Sse => {
+            let bind: std::net::SocketAddr = args
+                .bind
+                .parse()
+                .with_context(|| format!("invalid --bind address `{}`", args.bind))?;
+            transport_sse::serve(
+                server,
+                transport_sse::SseOptions {
+                    bind,
+                    auth_token: args.auth_token,
+                    cors_origins: args.cors_origins,
+                },
+            )
+            .await?;
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,152 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use log::info;
+use prometheus::{Encoder, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder};
+use rmcp::model::ErrorCode;
+use rmcp::ErrorData as McpError;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy)]
+enum ErrorCategory {
+    InvalidParams,
+    UpstreamHttp,
+}
+
+impl ErrorCategory {
+    fn as_str(self) -> &'static str {
+        match self {
+            ErrorCategory::InvalidParams => "invalid_params",
+            ErrorCategory::UpstreamHttp => "upstream_http",
+        }
+    }
+}
+
+pub struct Metrics {
+    registry: Registry,
+    tool_invocations: IntCounterVec,
+    tool_latency: HistogramVec,
+    endpoint_latency: HistogramVec,
+    tool_errors: IntCounterVec,
+}
+
+impl Metrics {
+    pub fn new() -> Arc<Self> {
+        let registry = Registry::new();
+        let tool_invocations = IntCounterVec::new(
+            Opts::new(
+                "siyuan_mcp_tool_invocations_total",
+                "Number of tool calls handled, by tool name",
+            ),
+            &["tool"],
+        )
+        .expect("valid metric");
+        let tool_latency = HistogramVec::new(
+            prometheus::HistogramOpts::new(
+                "siyuan_mcp_tool_latency_seconds",
+                "Tool call latency in seconds, by tool name",
+            ),
+            &["tool"],
+        )
+        .expect("valid metric");
+        let endpoint_latency = HistogramVec::new(
+            prometheus::HistogramOpts::new(
+                "siyuan_mcp_endpoint_latency_seconds",
+                "SiYuan API latency in seconds, by endpoint",
+            ),
+            &["endpoint"],
+        )
+        .expect("valid metric");
+        let tool_errors = IntCounterVec::new(
+            Opts::new(
+                "siyuan_mcp_tool_errors_total",
+                "Number of tool call errors, by tool name and category",
+            ),
+            &["tool", "category"],
+        )
+        .expect("valid metric");
+
+        registry
+            .register(Box::new(tool_invocations.clone()))
+            .expect("register metric");
+        registry
+            .register(Box::new(tool_latency.clone()))
+            .expect("register metric");
+        registry
+            .register(Box::new(endpoint_latency.clone()))
+            .expect("register metric");
+        registry
+            .register(Box::new(tool_errors.clone()))
+            .expect("register metric");
+
+        Arc::new(Self {
+            registry,
+            tool_invocations,
+            tool_latency,
+            endpoint_latency,
+            tool_errors,
+        })
+    }
+
+    pub fn record_invocation(&self, tool: &str, elapsed: Duration, outcome: &Result<Value, McpError>) {
+        self.tool_invocations.with_label_values(&[tool]).inc();
+        self.tool_latency
+            .with_label_values(&[tool])
+            .observe(elapsed.as_secs_f64());
+        if let Err(err) = outcome {
+            let category = classify_error(err);
+            self.tool_errors
+                .with_label_values(&[tool, category.as_str()])
+                .inc();
+        }
+    }
+
+    /// Observed by `SiyuanClient::post_json_value` around just the upstream HTTP
+    /// call, so this measures SiYuan's own latency rather than a tool handler's
+    /// surrounding local work (BM25 ranking, zip assembly, embedding calls, ...).
+    pub fn observe_endpoint_latency(&self, endpoint: &str, elapsed: Duration) {
+        self.endpoint_latency
+            .with_label_values(&[endpoint])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    fn encode(&self) -> String {
+        let encoder = TextEncoder::new();
+        let families = self.registry.gather();
+        let mut buffer = Vec::new();
+        encoder
+            .encode(&families, &mut buffer)
+            .expect("encode metrics");
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+/// Classifies by `McpError::code` rather than matching substrings in the message, so
+/// rewording an error (e.g. `exportMdContent returned no content`, which reads like an
+/// upstream failure but is `internal_error`/`INTERNAL_ERROR`) can't silently flip its category.
+fn classify_error(err: &McpError) -> ErrorCategory {
+    if err.code == ErrorCode::INVALID_PARAMS {
+        ErrorCategory::InvalidParams
+    } else {
+        ErrorCategory::UpstreamHttp
+    }
+}
+
+pub async fn serve(metrics: Arc<Metrics>, addr: SocketAddr) -> anyhow::Result<()> {
+    let app = Router::new()
+        .route("/metrics", get(metrics_handler))
+        .with_state(metrics);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("siyuan-mcp metrics endpoint listening on {}", addr);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn metrics_handler(State(metrics): State<Arc<Metrics>>) -> impl IntoResponse {
+    metrics.encode()
+}
@@ -0,0 +1,165 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use rmcp::ErrorData as McpError;
+use serde_json::{json, Value};
+
+use crate::SiyuanClient;
+
+const DEFAULT_LIMIT: u64 = 10;
+const FETCH_MULTIPLIER: u64 = 5;
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Runs a SQL `LIKE` search against SiYuan and re-ranks the hits locally with BM25.
+pub async fn handle(client: &SiyuanClient, sql_endpoint: &str, args: Value) -> Result<Value, McpError> {
+    let query = args
+        .get("query")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| McpError::invalid_params("missing or invalid `query`", None))?
+        .to_string();
+    let limit = args
+        .get("limit")
+        .and_then(|value| value.as_u64())
+        .unwrap_or(DEFAULT_LIMIT)
+        .max(1);
+    let highlight = args
+        .get("highlight")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+
+    let query_terms = tokenize(&query);
+    if query_terms.is_empty() {
+        return Ok(json!({ "hits": [] }));
+    }
+
+    let cache_bypass = args
+        .get("cache_bypass")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+
+    let stmt = build_search_stmt(&query_terms, limit * FETCH_MULTIPLIER);
+    let result = client
+        .post_json_value(sql_endpoint, json!({ "stmt": stmt, "cache_bypass": cache_bypass }))
+        .await?;
+    let rows = result
+        .get("data")
+        .and_then(|value| value.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let docs: Vec<(String, String, String, Vec<String>)> = rows
+        .iter()
+        .map(|row| {
+            let id = row.get("id").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            let content = row
+                .get("content")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let hpath = row
+                .get("hpath")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let tokens = tokenize(&content);
+            (id, hpath, content, tokens)
+        })
+        .collect();
+
+    let n = docs.len();
+    if n == 0 {
+        return Ok(json!({ "hits": [] }));
+    }
+    let avgdl = docs.iter().map(|doc| doc.3.len()).sum::<usize>() as f64 / n as f64;
+    if avgdl == 0.0 {
+        return Ok(json!({ "hits": [] }));
+    }
+
+    let mut doc_freq: HashMap<&str, usize> = HashMap::new();
+    for term in &query_terms {
+        let count = docs.iter().filter(|doc| doc.3.contains(term)).count();
+        doc_freq.insert(term.as_str(), count);
+    }
+
+    let mut hits: Vec<(f64, Value)> = docs
+        .iter()
+        .map(|(id, hpath, content, tokens)| {
+            let dl = tokens.len() as f64;
+            let score: f64 = query_terms
+                .iter()
+                .map(|term| {
+                    let f = tokens.iter().filter(|token| *token == term).count() as f64;
+                    if f == 0.0 {
+                        return 0.0;
+                    }
+                    let n_t = *doc_freq.get(term.as_str()).unwrap_or(&0) as f64;
+                    let idf = (((n as f64 - n_t + 0.5) / (n_t + 0.5)) + 1.0).ln().max(0.0);
+                    idf * (f * (K1 + 1.0)) / (f + K1 * (1.0 - B + B * dl / avgdl))
+                })
+                .sum();
+            let snippet = if highlight {
+                build_snippet(content, &query_terms)
+            } else {
+                content.chars().take(160).collect::<String>()
+            };
+            (
+                score,
+                json!({
+                    "id": id,
+                    "path": hpath,
+                    "score": score,
+                    "snippet": snippet,
+                }),
+            )
+        })
+        .collect();
+
+    hits.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+    hits.truncate(limit as usize);
+
+    Ok(json!({
+        "hits": hits.into_iter().map(|(_, hit)| hit).collect::<Vec<_>>(),
+    }))
+}
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(|token| token.to_lowercase())
+        .collect()
+}
+
+fn build_snippet(content: &str, terms: &[String]) -> String {
+    let lower = content.to_lowercase();
+    let best_byte = terms
+        .iter()
+        .filter_map(|term| lower.find(term.as_str()))
+        .min();
+    match best_byte {
+        Some(byte_pos) => {
+            let char_pos = content[..byte_pos].chars().count();
+            let chars: Vec<char> = content.chars().collect();
+            let start = char_pos.saturating_sub(40);
+            let end = (char_pos + 80).min(chars.len());
+            chars[start..end].iter().collect()
+        }
+        None => content.chars().take(160).collect(),
+    }
+}
+
+fn build_search_stmt(terms: &[String], limit: u64) -> String {
+    let clauses: Vec<String> = terms
+        .iter()
+        .map(|term| format!("content LIKE '%{}%'", escape_sql_literal(term)))
+        .collect();
+    format!(
+        "SELECT id, content, hpath FROM blocks WHERE {} ORDER BY updated DESC LIMIT {}",
+        clauses.join(" OR "),
+        limit
+    )
+}
+
+fn escape_sql_literal(value: &str) -> String {
+    value.replace('\'', "''")
+}
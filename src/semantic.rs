@@ -0,0 +1,271 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use rmcp::ErrorData as McpError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::SiyuanClient;
+
+const WINDOW_TOKENS: usize = 512;
+const WINDOW_OVERLAP: usize = 64;
+const DEFAULT_TOP_K: u64 = 5;
+
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, McpError>;
+}
+
+pub struct HttpEmbedder {
+    client: reqwest::Client,
+    endpoint: String,
+    model: String,
+}
+
+impl HttpEmbedder {
+    pub fn new(endpoint: String, model: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoint,
+            model,
+        }
+    }
+}
+
+#[async_trait]
+impl Embedder for HttpEmbedder {
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, McpError> {
+        let body = json!({ "model": self.model, "input": texts });
+        let resp = self
+            .client
+            .post(&self.endpoint)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|err| McpError::internal_error(err.to_string(), None))?;
+        let value: Value = resp
+            .json()
+            .await
+            .map_err(|err| McpError::internal_error(err.to_string(), None))?;
+        let data = value
+            .get("data")
+            .and_then(|value| value.as_array())
+            .ok_or_else(|| McpError::internal_error("embeddings response missing `data`", None))?;
+        data.iter()
+            .map(|item| {
+                item.get("embedding")
+                    .and_then(|value| value.as_array())
+                    .map(|values| {
+                        values
+                            .iter()
+                            .filter_map(|value| value.as_f64())
+                            .map(|value| value as f32)
+                            .collect()
+                    })
+                    .ok_or_else(|| {
+                        McpError::internal_error("embedding item missing `embedding`", None)
+                    })
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct IndexStore {
+    blocks: HashMap<String, IndexedBlock>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct IndexedBlock {
+    content_hash: u64,
+    windows: Vec<Window>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Window {
+    text: String,
+    vector: Vec<f32>,
+}
+
+pub struct SemanticIndex {
+    path: PathBuf,
+    embedder: Arc<dyn Embedder>,
+}
+
+impl SemanticIndex {
+    pub fn new(path: PathBuf, embedder: Arc<dyn Embedder>) -> Self {
+        Self { path, embedder }
+    }
+
+    fn load(&self) -> IndexStore {
+        std::fs::read(&self.path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, store: &IndexStore) -> Result<(), McpError> {
+        let bytes = serde_json::to_vec(store)
+            .map_err(|err| McpError::internal_error(err.to_string(), None))?;
+        std::fs::write(&self.path, bytes)
+            .map_err(|err| McpError::internal_error(err.to_string(), None))
+    }
+}
+
+pub async fn handle_build(
+    client: &SiyuanClient,
+    sql_endpoint: &str,
+    index: &SemanticIndex,
+    args: Value,
+) -> Result<Value, McpError> {
+    let stmt = args
+        .get("stmt")
+        .and_then(|value| value.as_str())
+        .unwrap_or("SELECT id, content FROM blocks")
+        .to_string();
+    // Bypass the read cache: a cached result here would make the incremental
+    // re-embed-only-changed-blocks logic silently see the same rows on every
+    // rebuild within the cache TTL.
+    let result = client
+        .post_json_value(sql_endpoint, json!({ "stmt": stmt, "cache_bypass": true }))
+        .await?;
+    let rows = result
+        .get("data")
+        .and_then(|value| value.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut store = index.load();
+    let mut embedded = 0usize;
+    let mut skipped = 0usize;
+
+    for row in &rows {
+        let id = row
+            .get("id")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let content = row
+            .get("content")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default()
+            .to_string();
+        if id.is_empty() {
+            continue;
+        }
+        let hash = content_hash(&content);
+        if store.blocks.get(&id).is_some_and(|existing| existing.content_hash == hash) {
+            skipped += 1;
+            continue;
+        }
+        let windows = split_windows(&content);
+        if windows.is_empty() {
+            continue;
+        }
+        let vectors = index.embedder.embed(&windows).await?;
+        let indexed_windows = windows
+            .into_iter()
+            .zip(vectors)
+            .map(|(text, vector)| Window { text, vector })
+            .collect();
+        store.blocks.insert(
+            id,
+            IndexedBlock {
+                content_hash: hash,
+                windows: indexed_windows,
+            },
+        );
+        embedded += 1;
+    }
+
+    index.save(&store)?;
+
+    Ok(json!({
+        "embedded": embedded,
+        "skipped": skipped,
+        "total_blocks": store.blocks.len(),
+    }))
+}
+
+pub async fn handle_search(index: &SemanticIndex, args: Value) -> Result<Value, McpError> {
+    let query = args
+        .get("query")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| McpError::invalid_params("missing or invalid `query`", None))?
+        .to_string();
+    let top_k = args
+        .get("top_k")
+        .and_then(|value| value.as_u64())
+        .unwrap_or(DEFAULT_TOP_K) as usize;
+
+    let query_vector = index
+        .embedder
+        .embed(&[query])
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| McpError::internal_error("embedder returned no vector", None))?;
+
+    let store = index.load();
+    let mut scored: Vec<(f32, String, String)> = store
+        .blocks
+        .iter()
+        .flat_map(|(id, block)| {
+            block.windows.iter().map(move |window| {
+                (
+                    cosine_similarity(&query_vector, &window.vector),
+                    id.clone(),
+                    window.text.clone(),
+                )
+            })
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+    scored.truncate(top_k);
+
+    Ok(json!({
+        "hits": scored
+            .into_iter()
+            .map(|(score, id, snippet)| json!({ "id": id, "score": score, "snippet": snippet }))
+            .collect::<Vec<_>>(),
+    }))
+}
+
+fn split_windows(content: &str) -> Vec<String> {
+    let words: Vec<&str> = content.split_whitespace().collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+    let mut windows = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = (start + WINDOW_TOKENS).min(words.len());
+        windows.push(words[start..end].join(" "));
+        if end == words.len() {
+            break;
+        }
+        start += WINDOW_TOKENS - WINDOW_OVERLAP;
+    }
+    windows
+}
+
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
@@ -0,0 +1,81 @@
+use std::borrow::Cow;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::{ToolKind, ToolSpec};
+
+impl FromStr for ToolKind {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "json" => Ok(ToolKind::Json),
+            "asset_upload" => Ok(ToolKind::AssetUpload),
+            "put_file" => Ok(ToolKind::PutFile),
+            "get_file" => Ok(ToolKind::GetFile),
+            "export_pod" => Ok(ToolKind::ExportPod),
+            "search" => Ok(ToolKind::Search),
+            "batch" => Ok(ToolKind::Batch),
+            "index_build" => Ok(ToolKind::IndexBuild),
+            "semantic_search" => Ok(ToolKind::SemanticSearch),
+            other => anyhow::bail!("unknown tool kind `{}`", other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ManifestToolSpec {
+    name: String,
+    endpoint: String,
+    kind: String,
+    description: String,
+    schema: String,
+}
+
+impl ManifestToolSpec {
+    fn into_tool_spec(self) -> anyhow::Result<ToolSpec> {
+        let kind = self
+            .kind
+            .parse::<ToolKind>()
+            .with_context(|| format!("tool `{}`", self.name))?;
+        Ok(ToolSpec {
+            name: Cow::Owned(self.name),
+            endpoint: Cow::Owned(self.endpoint),
+            kind,
+            description: Cow::Owned(self.description),
+            schema: Cow::Owned(self.schema),
+        })
+    }
+}
+
+fn parse_manifest(text: &str, path: &Path) -> anyhow::Result<Vec<ManifestToolSpec>> {
+    let is_toml = path.extension().and_then(|ext| ext.to_str()) == Some("toml");
+    if is_toml {
+        toml::from_str(text).with_context(|| format!("parse TOML tools file {}", path.display()))
+    } else {
+        serde_json::from_str(text)
+            .or_else(|_| toml::from_str(text))
+            .with_context(|| format!("parse tools file {}", path.display()))
+    }
+}
+
+/// Loads user-defined tool specs from `path` and merges them into `builtins`,
+/// with manifest entries overriding built-ins that share the same `name`.
+pub fn load_and_merge(builtins: &'static [ToolSpec], path: &Path) -> anyhow::Result<Vec<ToolSpec>> {
+    let text =
+        std::fs::read_to_string(path).with_context(|| format!("read tools file {}", path.display()))?;
+    let manifest = parse_manifest(&text, path)?;
+
+    let mut merged: Vec<ToolSpec> = builtins.to_vec();
+    for entry in manifest {
+        let spec = entry.into_tool_spec()?;
+        match merged.iter_mut().find(|existing| existing.name == spec.name) {
+            Some(existing) => *existing = spec,
+            None => merged.push(spec),
+        }
+    }
+    Ok(merged)
+}
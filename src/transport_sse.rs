@@ -0,0 +1,99 @@
+use std::net::SocketAddr;
+
+use anyhow::Context;
+use axum::extract::Request;
+use axum::http::{header, HeaderValue, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::Response;
+use log::info;
+use rmcp::transport::sse_server::{SseServer, SseServerConfig};
+use tower_http::cors::CorsLayer;
+
+use crate::SiyuanServer;
+
+pub struct SseOptions {
+    pub bind: SocketAddr,
+    pub auth_token: Option<String>,
+    pub cors_origins: Vec<String>,
+}
+
+pub async fn serve(server: SiyuanServer, options: SseOptions) -> anyhow::Result<()> {
+    let config = SseServerConfig {
+        bind: options.bind,
+        sse_path: "/sse".to_string(),
+        post_path: "/message".to_string(),
+        ct: Default::default(),
+        sse_keep_alive: None,
+    };
+    let ct = config.ct.clone();
+    let (sse_server, router) = SseServer::new(config);
+
+    // `Router::layer` makes the most-recently-added layer the outermost one, so CORS
+    // must be added last: a preflight `OPTIONS` request never carries `Authorization`
+    // and needs to be answered before bearer-auth rejects it.
+    let router = router
+        .layer(middleware::from_fn_with_state(
+            options.auth_token.clone(),
+            enforce_bearer_auth,
+        ))
+        .layer(cors_layer(&options.cors_origins));
+
+    let listener = tokio::net::TcpListener::bind(options.bind)
+        .await
+        .with_context(|| format!("bind SSE listener on {}", options.bind))?;
+    info!("siyuan-mcp SSE transport listening on {}", options.bind);
+
+    let http = tokio::spawn(async move { axum::serve(listener, router).await });
+
+    sse_server.with_service(move || server.clone());
+
+    tokio::signal::ctrl_c().await.ok();
+    ct.cancel();
+    let _ = http.await;
+    Ok(())
+}
+
+async fn enforce_bearer_auth(
+    axum::extract::State(auth_token): axum::extract::State<Option<String>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let Some(expected) = auth_token else {
+        return Ok(next.run(request).await);
+    };
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or("");
+    if !constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    Ok(next.run(request).await)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b.iter()) {
+        diff |= x ^ y;
+    }
+    diff == 0
+}
+
+fn cors_layer(origins: &[String]) -> CorsLayer {
+    if origins.is_empty() {
+        return CorsLayer::new();
+    }
+    let allowed: Vec<HeaderValue> = origins
+        .iter()
+        .filter_map(|origin| origin.parse::<HeaderValue>().ok())
+        .collect();
+    CorsLayer::new()
+        .allow_origin(allowed)
+        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+        .allow_headers(tower_http::cors::Any)
+}
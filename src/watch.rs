@@ -0,0 +1,251 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{debug, warn};
+use rmcp::model::RawResourceUpdatedNotificationParam;
+use rmcp::service::Peer;
+use rmcp::{ErrorData as McpError, RoleServer};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use crate::SiyuanClient;
+
+/// Durable cursor: the high-water-mark `updated` timestamp plus a content hash per
+/// known block ID, so a restart resumes without replaying the whole store.
+///
+/// `blocks.updated` is a fixed-width `YYYYMMDDHHMMSS` TEXT column in SiYuan's sqlite
+/// store (returned by `/api/query/sql` as a JSON string, not a number), so the
+/// high-water mark is kept and compared as a string rather than parsed as `i64`.
+#[derive(Serialize, Deserialize, Default, Clone)]
+struct WatchCursor {
+    last_updated: String,
+    known_blocks: HashMap<String, u64>,
+}
+
+struct WatchState {
+    cursor: WatchCursor,
+    predicate: Option<String>,
+    peer: Option<Peer<RoleServer>>,
+    started: bool,
+}
+
+pub struct WatchManager {
+    state_file: PathBuf,
+    interval_ms: u64,
+    state: Mutex<WatchState>,
+}
+
+impl WatchManager {
+    pub fn load(state_file: PathBuf, interval_ms: u64) -> Arc<Self> {
+        let cursor = std::fs::read(&state_file)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Arc::new(Self {
+            state_file,
+            interval_ms,
+            state: Mutex::new(WatchState {
+                cursor,
+                predicate: None,
+                peer: None,
+                started: false,
+            }),
+        })
+    }
+
+    async fn persist(&self, cursor: &WatchCursor) {
+        if let Ok(bytes) = serde_json::to_vec(cursor) {
+            let _ = tokio::fs::write(&self.state_file, bytes).await;
+        }
+    }
+
+    /// Registers the subscribing peer and predicate, lazily starting the poll loop
+    /// the first time a subscriber shows up (a no-op if `--watch-interval-ms` is 0).
+    pub async fn subscribe(
+        self: &Arc<Self>,
+        client: Arc<SiyuanClient>,
+        peer: Peer<RoleServer>,
+        predicate: Option<String>,
+    ) -> Value {
+        let mut state = self.state.lock().await;
+        state.predicate = predicate.clone();
+        state.peer = Some(peer);
+        let already_started = state.started;
+        state.started = true;
+        drop(state);
+
+        if !already_started && self.interval_ms > 0 {
+            let manager = self.clone();
+            tokio::spawn(async move { manager.poll_loop(client).await });
+        }
+
+        json!({
+            "watching": self.interval_ms > 0,
+            "interval_ms": self.interval_ms,
+            "predicate": predicate,
+        })
+    }
+
+    async fn poll_loop(self: Arc<Self>, client: Arc<SiyuanClient>) {
+        let mut interval = tokio::time::interval(Duration::from_millis(self.interval_ms));
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.tick(&client).await {
+                warn!("watch poll failed: {}", err);
+            }
+        }
+    }
+
+    async fn tick(&self, client: &SiyuanClient) -> Result<(), McpError> {
+        let (predicate, mut cursor) = {
+            let state = self.state.lock().await;
+            (state.predicate.clone(), state.cursor.clone())
+        };
+
+        // Bypass the read cache: this poll exists to surface edits made outside the
+        // MCP server, which never call `invalidate_all`, so a cached response here
+        // would silently stop reflecting external changes until the TTL expires.
+        let changed = client
+            .post_json_value(
+                "/api/query/sql",
+                json!({
+                    "stmt": changed_blocks_stmt(&predicate, &cursor.last_updated),
+                    "cache_bypass": true,
+                }),
+            )
+            .await?;
+        let rows = changed
+            .get("data")
+            .and_then(|value| value.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        let mut created = Vec::new();
+        let mut updated = Vec::new();
+        for row in &rows {
+            let id = row
+                .get("id")
+                .and_then(|value| value.as_str())
+                .unwrap_or_default()
+                .to_string();
+            if id.is_empty() {
+                continue;
+            }
+            let content = row
+                .get("content")
+                .and_then(|value| value.as_str())
+                .unwrap_or_default();
+            let row_updated = row
+                .get("updated")
+                .and_then(|value| value.as_str())
+                .unwrap_or_default();
+            let hash = content_hash(content);
+            match cursor.known_blocks.insert(id.clone(), hash) {
+                None => created.push(id),
+                Some(previous) if previous != hash => updated.push(id),
+                Some(_) => {}
+            }
+            if row_updated > cursor.last_updated.as_str() {
+                cursor.last_updated = row_updated.to_string();
+            }
+        }
+
+        let live = client
+            .post_json_value(
+                "/api/query/sql",
+                json!({ "stmt": live_ids_stmt(&predicate), "cache_bypass": true }),
+            )
+            .await?;
+        let live_ids: HashSet<String> = live
+            .get("data")
+            .and_then(|value| value.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|row| row.get("id").and_then(|value| value.as_str()).map(str::to_string))
+            .collect();
+        let deleted: Vec<String> = cursor
+            .known_blocks
+            .keys()
+            .filter(|id| !live_ids.contains(*id))
+            .cloned()
+            .collect();
+        for id in &deleted {
+            cursor.known_blocks.remove(id);
+        }
+
+        self.persist(&cursor).await;
+        let peer = {
+            let mut state = self.state.lock().await;
+            state.cursor = cursor;
+            state.peer.clone()
+        };
+
+        if created.is_empty() && updated.is_empty() && deleted.is_empty() {
+            return Ok(());
+        }
+        debug!(
+            "watch tick: {} created, {} updated, {} deleted",
+            created.len(),
+            updated.len(),
+            deleted.len()
+        );
+        if let Some(peer) = peer {
+            for id in created.iter().chain(updated.iter()) {
+                let _ = peer
+                    .notify_resource_updated(RawResourceUpdatedNotificationParam {
+                        uri: format!("siyuan://block/{}", id),
+                    })
+                    .await;
+            }
+            if !deleted.is_empty() {
+                let _ = peer.notify_resource_list_changed().await;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn changed_blocks_stmt(predicate: &Option<String>, since: &str) -> String {
+    match predicate {
+        Some(predicate) if !predicate.trim().is_empty() => format!(
+            "SELECT id, content, updated FROM blocks WHERE updated > '{}' AND ({}) ORDER BY updated",
+            since, predicate
+        ),
+        _ => format!(
+            "SELECT id, content, updated FROM blocks WHERE updated > '{}' ORDER BY updated",
+            since
+        ),
+    }
+}
+
+fn live_ids_stmt(predicate: &Option<String>) -> String {
+    match predicate {
+        Some(predicate) if !predicate.trim().is_empty() => {
+            format!("SELECT id FROM blocks WHERE {}", predicate)
+        }
+        _ => "SELECT id FROM blocks".to_string(),
+    }
+}
+
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+pub async fn handle_subscribe(
+    client: Arc<SiyuanClient>,
+    manager: &Arc<WatchManager>,
+    peer: Peer<RoleServer>,
+    args: Value,
+) -> Result<Value, McpError> {
+    let predicate = args
+        .get("predicate")
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string());
+    Ok(manager.subscribe(client, peer, predicate).await)
+}